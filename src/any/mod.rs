@@ -0,0 +1,322 @@
+mod router;
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use image::DynamicImage;
+use url::Url;
+
+pub use self::router::*;
+use crate::{
+    Author, Bookshelf, Category, ChapterInfo, ChapterPurchase, CiweimaoClient, CiyuanjiClient,
+    Client, ClientBuilder, Comment, ContentInfos, Error, NovelInfo, NovelSummary, Options,
+    PurchaseRecord, RankingKind, RankingPeriod, ReadingProgress, Review, SfacgClient, Tag,
+    UserInfo, VolumeInfo, VolumeInfos,
+};
+
+/// The sources supported by this crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// <https://www.sfacg.com>
+    Sfacg,
+    /// <https://www.ciweimao.com>
+    Ciweimao,
+    /// <https://www.ciyuanji.com>
+    Ciyuanji,
+}
+
+impl Source {
+    /// All supported sources
+    pub const ALL: [Source; 3] = [Source::Sfacg, Source::Ciweimao, Source::Ciyuanji];
+
+    /// The name of the source, also the name of its config and data directories
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Source::Sfacg => "sfacg",
+            Source::Ciweimao => "ciweimao",
+            Source::Ciyuanji => "ciyuanji",
+        }
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Source {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Source::ALL
+            .into_iter()
+            .find(|source| source.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| Error::Unsupported(format!("source {s}")))
+    }
+}
+
+/// A client of any source, selected at runtime
+#[must_use]
+pub enum AnyClient {
+    /// Sfacg client
+    Sfacg(SfacgClient),
+    /// Ciweimao client
+    Ciweimao(CiweimaoClient),
+    /// Ciyuanji client
+    Ciyuanji(CiyuanjiClient),
+}
+
+macro_rules! dispatch {
+    ($self:ident, $client:ident => $expr:expr) => {
+        match $self {
+            AnyClient::Sfacg($client) => $expr,
+            AnyClient::Ciweimao($client) => $expr,
+            AnyClient::Ciyuanji($client) => $expr,
+        }
+    };
+}
+
+impl AnyClient {
+    /// Create a client of the source
+    pub async fn new(source: Source) -> Result<Self, Error> {
+        Self::builder().build(source).await
+    }
+
+    /// Create a builder of clients of any source
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::default()
+    }
+
+    /// The source of the client
+    #[must_use]
+    pub fn source(&self) -> Source {
+        match self {
+            AnyClient::Sfacg(_) => Source::Sfacg,
+            AnyClient::Ciweimao(_) => Source::Ciweimao,
+            AnyClient::Ciyuanji(_) => Source::Ciyuanji,
+        }
+    }
+}
+
+impl ClientBuilder<AnyClient> {
+    /// Check the options and create the client of the source
+    pub async fn build(self, source: Source) -> Result<AnyClient, Error> {
+        Ok(match source {
+            Source::Sfacg => AnyClient::Sfacg(self.cast::<SfacgClient>().build().await?),
+            Source::Ciweimao => AnyClient::Ciweimao(self.cast::<CiweimaoClient>().build().await?),
+            Source::Ciyuanji => AnyClient::Ciyuanji(self.cast::<CiyuanjiClient>().build().await?),
+        })
+    }
+}
+
+impl From<SfacgClient> for AnyClient {
+    fn from(client: SfacgClient) -> Self {
+        AnyClient::Sfacg(client)
+    }
+}
+
+impl From<CiweimaoClient> for AnyClient {
+    fn from(client: CiweimaoClient) -> Self {
+        AnyClient::Ciweimao(client)
+    }
+}
+
+impl From<CiyuanjiClient> for AnyClient {
+    fn from(client: CiyuanjiClient) -> Self {
+        AnyClient::Ciyuanji(client)
+    }
+}
+
+impl Client for AnyClient {
+    async fn shutdown(&self) -> Result<(), Error> {
+        dispatch!(self, client => client.shutdown().await)
+    }
+
+    async fn add_cookie(&self, cookie_str: &str, url: &Url) -> Result<(), Error> {
+        dispatch!(self, client => client.add_cookie(cookie_str, url).await)
+    }
+
+    async fn log_in(&self, username: String, password: Option<String>) -> Result<(), Error> {
+        dispatch!(self, client => client.log_in(username, password).await)
+    }
+
+    async fn logged_in(&self) -> Result<bool, Error> {
+        dispatch!(self, client => client.logged_in().await)
+    }
+
+    async fn user_info(&self) -> Result<UserInfo, Error> {
+        dispatch!(self, client => client.user_info().await)
+    }
+
+    async fn money(&self) -> Result<u32, Error> {
+        dispatch!(self, client => client.money().await)
+    }
+
+    async fn sign_in(&self) -> Result<(), Error> {
+        dispatch!(self, client => client.sign_in().await)
+    }
+
+    async fn bookshelf_infos(&self) -> Result<Vec<u32>, Error> {
+        dispatch!(self, client => client.bookshelf_infos().await)
+    }
+
+    async fn bookshelves(&self) -> Result<Vec<Bookshelf>, Error> {
+        dispatch!(self, client => client.bookshelves().await)
+    }
+
+    async fn add_to_bookshelf(&self, novel_id: u32, shelf_id: u32) -> Result<(), Error> {
+        dispatch!(self, client => client.add_to_bookshelf(novel_id, shelf_id).await)
+    }
+
+    async fn remove_from_bookshelf(&self, novel_id: u32, shelf_id: u32) -> Result<(), Error> {
+        dispatch!(self, client => client.remove_from_bookshelf(novel_id, shelf_id).await)
+    }
+
+    async fn move_between_shelves(
+        &self,
+        novel_id: u32,
+        from_shelf_id: u32,
+        to_shelf_id: u32,
+    ) -> Result<(), Error> {
+        dispatch!(self, client => {
+            client
+                .move_between_shelves(novel_id, from_shelf_id, to_shelf_id)
+                .await
+        })
+    }
+
+    async fn novel_info(&self, id: u32) -> Result<Option<NovelInfo>, Error> {
+        dispatch!(self, client => client.novel_info(id).await)
+    }
+
+    async fn author_info(&self, id: u32) -> Result<Option<Author>, Error> {
+        dispatch!(self, client => client.author_info(id).await)
+    }
+
+    async fn author_novels(&self, id: u32) -> Result<Vec<NovelSummary>, Error> {
+        dispatch!(self, client => client.author_novels(id).await)
+    }
+
+    async fn volume_infos(&self, id: u32) -> Result<Option<VolumeInfos>, Error> {
+        dispatch!(self, client => client.volume_infos(id).await)
+    }
+
+    async fn cached_novel_info(&self, id: u32) -> Result<Option<NovelInfo>, Error> {
+        dispatch!(self, client => client.cached_novel_info(id).await)
+    }
+
+    async fn cached_volume_infos(&self, id: u32) -> Result<Option<VolumeInfos>, Error> {
+        dispatch!(self, client => client.cached_volume_infos(id).await)
+    }
+
+    async fn content_infos(&self, info: &ChapterInfo) -> Result<ContentInfos, Error> {
+        dispatch!(self, client => client.content_infos(info).await)
+    }
+
+    async fn chapter_comments(
+        &self,
+        info: &ChapterInfo,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error> {
+        dispatch!(self, client => client.chapter_comments(info, page).await)
+    }
+
+    async fn paragraph_comment_counts(
+        &self,
+        info: &ChapterInfo,
+    ) -> Result<HashMap<usize, u32>, Error> {
+        dispatch!(self, client => client.paragraph_comment_counts(info).await)
+    }
+
+    async fn paragraph_comments(
+        &self,
+        info: &ChapterInfo,
+        index: usize,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error> {
+        dispatch!(self, client => client.paragraph_comments(info, index, page).await)
+    }
+
+    async fn novel_reviews(&self, novel_id: u32, page: u16) -> Result<Option<Vec<Review>>, Error> {
+        dispatch!(self, client => client.novel_reviews(novel_id, page).await)
+    }
+
+    async fn review_replies(
+        &self,
+        review_id: u32,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error> {
+        dispatch!(self, client => client.review_replies(review_id, page).await)
+    }
+
+    async fn reading_progress(&self, novel_id: u32) -> Result<Option<ReadingProgress>, Error> {
+        dispatch!(self, client => client.reading_progress(novel_id).await)
+    }
+
+    async fn set_reading_progress(&self, novel_id: u32, chapter_id: u32) -> Result<(), Error> {
+        dispatch!(self, client => client.set_reading_progress(novel_id, chapter_id).await)
+    }
+
+    async fn buy_chapter(&self, info: &ChapterInfo) -> Result<(), Error> {
+        dispatch!(self, client => client.buy_chapter(info).await)
+    }
+
+    async fn buy_chapters(&self, infos: &[ChapterInfo]) -> Result<Vec<ChapterPurchase>, Error> {
+        dispatch!(self, client => client.buy_chapters(infos).await)
+    }
+
+    async fn buy_volume(&self, info: &VolumeInfo) -> Result<Vec<ChapterPurchase>, Error> {
+        dispatch!(self, client => client.buy_volume(info).await)
+    }
+
+    async fn buy_all_remaining(&self, novel_id: u32) -> Result<Vec<ChapterPurchase>, Error> {
+        dispatch!(self, client => client.buy_all_remaining(novel_id).await)
+    }
+
+    async fn purchase_records(&self) -> Result<Vec<PurchaseRecord>, Error> {
+        dispatch!(self, client => client.purchase_records().await)
+    }
+
+    async fn image(&self, url: &Url) -> Result<DynamicImage, Error> {
+        dispatch!(self, client => client.image(url).await)
+    }
+
+    async fn categories(&self) -> Result<&Vec<Category>, Error> {
+        dispatch!(self, client => client.categories().await)
+    }
+
+    async fn tags(&self) -> Result<&Vec<Tag>, Error> {
+        dispatch!(self, client => client.tags().await)
+    }
+
+    async fn search_infos(
+        &self,
+        option: &Options,
+        page: u16,
+        size: u16,
+    ) -> Result<Option<Vec<u32>>, Error> {
+        dispatch!(self, client => client.search_infos(option, page, size).await)
+    }
+
+    async fn search_summaries(
+        &self,
+        option: &Options,
+        page: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        dispatch!(self, client => client.search_summaries(option, page).await)
+    }
+
+    async fn rankings(
+        &self,
+        kind: RankingKind,
+        period: RankingPeriod,
+        page: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        dispatch!(self, client => client.rankings(kind, period, page).await)
+    }
+}
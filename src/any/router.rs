@@ -0,0 +1,217 @@
+use std::str::FromStr;
+
+use url::Url;
+
+use super::Source;
+use crate::Error;
+
+/// What a share url or a `source:id` string points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// A novel and its id
+    Novel(u32),
+    /// A chapter, the novel id is only known if the url contains it
+    Chapter {
+        /// Id of the novel the chapter belongs to
+        novel_id: Option<u32>,
+        /// Id of the chapter
+        chapter_id: u32,
+    },
+}
+
+/// A source and the resource of it, returned by `route`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Route {
+    /// The source of the resource
+    pub source: Source,
+    /// The novel or chapter
+    pub target: Target,
+}
+
+impl FromStr for Route {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        route(s)
+    }
+}
+
+/// Find the source and the novel or chapter of a share url, or of a `source:id` string
+///
+/// e.g. `https://book.sfacg.com/Novel/263060/`, `https://www.ciweimao.com/chapter/107233436`
+/// or `ciyuanji:10993`
+pub fn route<T>(input: T) -> Result<Route, Error>
+where
+    T: AsRef<str>,
+{
+    let input = input.as_ref().trim();
+    let unsupported = || Error::Unsupported(format!("novel or chapter {input}"));
+
+    if let Some((source, id)) = input.split_once(':') {
+        if let Ok(source) = Source::from_str(source) {
+            let id = id.trim().parse().map_err(|_| unsupported())?;
+
+            return Ok(Route {
+                source,
+                target: Target::Novel(id),
+            });
+        }
+    }
+
+    let url = Url::parse(input).map_err(|_| unsupported())?;
+    let host = url.host_str().ok_or_else(unsupported)?;
+    let segments: Vec<_> = url
+        .path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+        .unwrap_or_default();
+
+    let (source, target) = if is_host(host, "sfacg.com") {
+        (Source::Sfacg, sfacg_target(&segments))
+    } else if is_host(host, "ciweimao.com") {
+        (Source::Ciweimao, ciweimao_target(&segments))
+    } else if is_host(host, "ciyuanji.com") {
+        (Source::Ciyuanji, ciyuanji_target(&segments))
+    } else {
+        return Err(unsupported());
+    };
+
+    Ok(Route {
+        source,
+        target: target.ok_or_else(unsupported)?,
+    })
+}
+
+fn is_host(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{domain}"))
+}
+
+fn id(segment: &str) -> Option<u32> {
+    segment.parse().ok()
+}
+
+/// `/Novel/263060/`, `/Novel/263060/MainIndex/`, `/Novel/263060/507358/4445428/`,
+/// `/vip/c/4445428/`, `/b/263060/` and `/c/4445428/`
+fn sfacg_target(segments: &[&str]) -> Option<Target> {
+    match segments {
+        ["Novel", novel_id, _, chapter_id] => Some(Target::Chapter {
+            novel_id: Some(id(novel_id)?),
+            chapter_id: id(chapter_id)?,
+        }),
+        ["Novel" | "b", novel_id, ..] => Some(Target::Novel(id(novel_id)?)),
+        ["vip", "c", chapter_id] | ["c", chapter_id] => Some(Target::Chapter {
+            novel_id: None,
+            chapter_id: id(chapter_id)?,
+        }),
+        _ => None,
+    }
+}
+
+/// `/book/100011781`, `/chapter-list/100011781` and `/chapter/107233436`
+fn ciweimao_target(segments: &[&str]) -> Option<Target> {
+    match segments {
+        ["book" | "chapter-list", novel_id, ..] => Some(Target::Novel(id(novel_id)?)),
+        ["chapter", chapter_id, ..] => Some(Target::Chapter {
+            novel_id: None,
+            chapter_id: id(chapter_id)?,
+        }),
+        _ => None,
+    }
+}
+
+/// `/b_d_10993.html` and `/chapter/10993_1234567.html`
+fn ciyuanji_target(segments: &[&str]) -> Option<Target> {
+    match segments {
+        [page] => {
+            let novel_id = page.strip_prefix("b_d_")?.strip_suffix(".html")?;
+
+            Some(Target::Novel(id(novel_id)?))
+        }
+        ["chapter", page] => {
+            let (novel_id, chapter_id) = page.strip_suffix(".html")?.split_once('_')?;
+
+            Some(Target::Chapter {
+                novel_id: Some(id(novel_id)?),
+                chapter_id: id(chapter_id)?,
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn novel(source: Source, id: u32) -> Route {
+        Route {
+            source,
+            target: Target::Novel(id),
+        }
+    }
+
+    fn chapter(source: Source, novel_id: Option<u32>, chapter_id: u32) -> Route {
+        Route {
+            source,
+            target: Target::Chapter {
+                novel_id,
+                chapter_id,
+            },
+        }
+    }
+
+    #[test]
+    fn source_id() -> Result<(), Error> {
+        assert_eq!(route("sfacg:263060")?, novel(Source::Sfacg, 263060));
+        assert_eq!(
+            route(" Ciweimao:100011781 ")?,
+            novel(Source::Ciweimao, 100011781)
+        );
+        assert!(matches!(route("sfacg:abc"), Err(Error::Unsupported(_))));
+        assert!(matches!(route("unknown:1"), Err(Error::Unsupported(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn urls() -> Result<(), Error> {
+        assert_eq!(
+            route("https://book.sfacg.com/Novel/263060/MainIndex/")?,
+            novel(Source::Sfacg, 263060)
+        );
+        assert_eq!(
+            route("https://m.sfacg.com/b/263060/")?,
+            novel(Source::Sfacg, 263060)
+        );
+        assert_eq!(
+            route("https://book.sfacg.com/Novel/263060/507358/4445428/")?,
+            chapter(Source::Sfacg, Some(263060), 4445428)
+        );
+        assert_eq!(
+            route("https://book.sfacg.com/vip/c/4445428/")?,
+            chapter(Source::Sfacg, None, 4445428)
+        );
+        assert_eq!(
+            route("https://www.ciweimao.com/book/100011781")?,
+            novel(Source::Ciweimao, 100011781)
+        );
+        assert_eq!(
+            route("https://wap.ciweimao.com/chapter/107233436")?,
+            chapter(Source::Ciweimao, None, 107233436)
+        );
+        assert_eq!(
+            route("https://www.ciyuanji.com/b_d_10993.html")?,
+            novel(Source::Ciyuanji, 10993)
+        );
+        assert_eq!(
+            route("https://www.ciyuanji.com/chapter/10993_1234567.html")?,
+            chapter(Source::Ciyuanji, Some(10993), 1234567)
+        );
+
+        assert!(route("https://www.example.com/book/1").is_err());
+        assert!(route("https://www.ciweimao.com/").is_err());
+
+        Ok(())
+    }
+}
@@ -4,7 +4,6 @@ mod utils;
 
 use std::{
     io::Cursor,
-    path::PathBuf,
     sync::RwLock,
     time::{SystemTime, UNIX_EPOCH},
 };
@@ -22,9 +21,11 @@ use url::Url;
 
 use self::structure::*;
 use crate::{
-    Category, ChapterInfo, Client, ContentInfo, ContentInfos, Error, FindImageResult,
-    FindTextResult, HTTPClient, NovelDB, NovelInfo, Options, Tag, UserInfo, VolumeInfo,
-    VolumeInfos, WordCountRange,
+    Author, Bookshelf, CacheKey, Category, ChapterInfo, ChapterPurchase, Client, ClientOptions,
+    Comment, ContentInfo, ContentInfos, Error, FindImageResult, FindTextResult, Freshness,
+    HTTPClient, NovelDB, NovelInfo, NovelSummary, Options, PurchaseRecord, RankingKind,
+    RankingPeriod, ReadingProgress, Review, SortOrder, Tag, UserInfo, VolumeInfo, VolumeInfos,
+    WordCountRange,
 };
 
 #[must_use]
@@ -37,9 +38,7 @@ pub(crate) struct Config {
 /// Ciweimao client, use it to access Apis
 #[must_use]
 pub struct CiweimaoClient {
-    proxy: Option<Url>,
-    no_proxy: bool,
-    cert_path: Option<PathBuf>,
+    options: ClientOptions,
 
     client: OnceCell<HTTPClient>,
     client_rss: OnceCell<HTTPClient>,
@@ -50,18 +49,6 @@ pub struct CiweimaoClient {
 }
 
 impl Client for CiweimaoClient {
-    fn proxy(&mut self, proxy: Url) {
-        self.proxy = Some(proxy);
-    }
-
-    fn no_proxy(&mut self) {
-        self.no_proxy = true;
-    }
-
-    fn cert(&mut self, cert_path: PathBuf) {
-        self.cert_path = Some(cert_path);
-    }
-
     async fn shutdown(&self) -> Result<(), Error> {
         self.client().await?.shutdown()?;
         self.do_shutdown()?;
@@ -114,6 +101,10 @@ impl Client for CiweimaoClient {
     }
 
     async fn user_info(&self) -> Result<UserInfo, Error> {
+        if self.options.offline {
+            return self.db().await?.find_cache(CacheKey::UserInfo).await;
+        }
+
         let response: UserInfoResponse = self.post("/reader/get_my_info", EmptyRequest {}).await?;
         utils::check_response_success(response.code, response.tip)?;
         let reader_info = response.data.unwrap().reader_info;
@@ -123,6 +114,11 @@ impl Client for CiweimaoClient {
             avatar: reader_info.avatar_url,
         };
 
+        self.db()
+            .await?
+            .save_cache(CacheKey::UserInfo, &user_info)
+            .await?;
+
         Ok(user_info)
     }
 
@@ -155,10 +151,25 @@ impl Client for CiweimaoClient {
     }
 
     async fn bookshelf_infos(&self) -> Result<Vec<u32>, Error> {
-        let shelf_ids = self.shelf_list().await?;
-        let mut result = Vec::new();
+        Ok(self
+            .bookshelves()
+            .await?
+            .into_iter()
+            .flat_map(|bookshelf| bookshelf.novel_ids)
+            .collect())
+    }
+
+    async fn bookshelves(&self) -> Result<Vec<Bookshelf>, Error> {
+        if self.options.offline {
+            return self.db().await?.find_cache(CacheKey::Bookshelves).await;
+        }
+
+        let shelf_list = self.shelf_list().await?;
+        let mut result = Vec::with_capacity(shelf_list.len());
+
+        for shelf in shelf_list {
+            let shelf_id = shelf.shelf_id.parse()?;
 
-        for shelf_id in shelf_ids {
             let response: BookshelfResponse = self
                 .post(
                     "/bookshelf/get_shelf_book_list_new",
@@ -172,17 +183,89 @@ impl Client for CiweimaoClient {
                 .await?;
             utils::check_response_success(response.code, response.tip)?;
 
+            let mut novel_ids = Vec::new();
             for novel_info in response.data.unwrap().book_list {
-                result.push(novel_info.book_info.book_id.parse()?);
+                novel_ids.push(novel_info.book_info.book_id.parse()?);
             }
+
+            result.push(Bookshelf {
+                id: shelf_id,
+                name: shelf.shelf_name.trim().to_string(),
+                novel_ids,
+            });
         }
 
+        self.db()
+            .await?
+            .save_cache(CacheKey::Bookshelves, &result)
+            .await?;
+
         Ok(result)
     }
 
+    async fn add_to_bookshelf(&self, novel_id: u32, shelf_id: u32) -> Result<(), Error> {
+        let response: GenericResponse = self
+            .post(
+                "/bookshelf/favor",
+                ShelfBookRequest {
+                    shelf_id,
+                    book_id: novel_id,
+                },
+            )
+            .await?;
+        utils::check_response_success(response.code, response.tip)?;
+
+        Ok(())
+    }
+
+    async fn remove_from_bookshelf(&self, novel_id: u32, shelf_id: u32) -> Result<(), Error> {
+        let response: GenericResponse = self
+            .post(
+                "/bookshelf/delete_shelf_book",
+                ShelfBookRequest {
+                    shelf_id,
+                    book_id: novel_id,
+                },
+            )
+            .await?;
+        utils::check_response_success(response.code, response.tip)?;
+
+        Ok(())
+    }
+
+    async fn move_between_shelves(
+        &self,
+        novel_id: u32,
+        from_shelf_id: u32,
+        to_shelf_id: u32,
+    ) -> Result<(), Error> {
+        let response: GenericResponse = self
+            .post(
+                "/bookshelf/move_shelf_books",
+                MoveShelfBookRequest {
+                    from_shelf_id,
+                    to_shelf_id,
+                    book_id: novel_id,
+                },
+            )
+            .await?;
+        utils::check_response_success(response.code, response.tip)?;
+
+        Ok(())
+    }
+
     async fn novel_info(&self, id: u32) -> Result<Option<NovelInfo>, Error> {
         assert!(id > 0);
 
+        if let Some(novel_info) = self
+            .db()
+            .await?
+            .find_novel_info(id, self.metadata_freshness())
+            .await?
+        {
+            return Ok(Some(novel_info));
+        }
+
         let response: NovelInfoResponse = self
             .post("/book/get_info_by_id", NovelInfoRequest { book_id: id })
             .await?;
@@ -195,6 +278,7 @@ impl Client for CiweimaoClient {
         let novel_info = NovelInfo {
             id,
             name: data.book_name.trim().to_string(),
+            author_id: data.author_id.map(|id| id.parse()).transpose()?,
             author_name: data.author_name.trim().to_string(),
             cover_url: data.cover,
             introduction: CiweimaoClient::parse_introduction(data.description),
@@ -207,10 +291,57 @@ impl Client for CiweimaoClient {
             tags: self.parse_tags(data.tag_list).await?,
         };
 
+        self.db().await?.save_novel_info(&novel_info).await?;
+
         Ok(Some(novel_info))
     }
 
+    async fn author_info(&self, id: u32) -> Result<Option<Author>, Error> {
+        let response: AuthorResponse = self
+            .post("/author/get_author_info", AuthorRequest { author_id: id })
+            .await?;
+        if response.code == CiweimaoClient::NOT_FOUND {
+            return Ok(None);
+        }
+        utils::check_response_success(response.code, response.tip)?;
+
+        let author_info = response.data.unwrap().author_info;
+        Ok(Some(Author {
+            id: author_info.author_id.parse()?,
+            name: author_info.author_name.trim().to_string(),
+            avatar: author_info.avatar_url,
+            introduction: CiweimaoClient::parse_introduction(author_info.author_intro),
+            follower_count: Some(author_info.fans_num.parse()?),
+        }))
+    }
+
+    async fn author_novels(&self, id: u32) -> Result<Vec<NovelSummary>, Error> {
+        let response: SearchResponse = self
+            .post(
+                "/author/get_author_book_list",
+                AuthorRequest { author_id: id },
+            )
+            .await?;
+        utils::check_response_success(response.code, response.tip)?;
+
+        let mut result = Vec::new();
+        for novel_info in response.data.unwrap().book_list {
+            result.push(self.parse_summary(novel_info).await?);
+        }
+
+        Ok(result)
+    }
+
     async fn volume_infos(&self, id: u32) -> Result<Option<VolumeInfos>, Error> {
+        if let Some(volume_infos) = self
+            .db()
+            .await?
+            .find_volume_infos(id, self.metadata_freshness())
+            .await?
+        {
+            return Ok(Some(volume_infos));
+        }
+
         let response: VolumesResponse = self
             .post(
                 "/chapter/get_updated_chapter_by_division_new",
@@ -259,9 +390,28 @@ impl Client for CiweimaoClient {
             volume_infos.push(volume_info);
         }
 
+        self.db()
+            .await?
+            .save_volume_infos(id, &volume_infos)
+            .await?;
+
         Ok(Some(volume_infos))
     }
 
+    async fn cached_novel_info(&self, id: u32) -> Result<Option<NovelInfo>, Error> {
+        self.db()
+            .await?
+            .find_novel_info(id, Freshness::Forever)
+            .await
+    }
+
+    async fn cached_volume_infos(&self, id: u32) -> Result<Option<VolumeInfos>, Error> {
+        self.db()
+            .await?
+            .find_volume_infos(id, Freshness::Forever)
+            .await
+    }
+
     async fn content_infos(&self, info: &ChapterInfo) -> Result<ContentInfos, Error> {
         let content;
 
@@ -269,6 +419,9 @@ impl Client for CiweimaoClient {
             FindTextResult::Ok(str) => {
                 content = str;
             }
+            FindTextResult::Outdate if self.options.offline => {
+                content = self.db().await?.find_saved_text(info).await?;
+            }
             other => {
                 let cmd = self.chapter_cmd(info.id).await?;
                 let key = crate::sha256(cmd.as_bytes());
@@ -316,12 +469,207 @@ impl Client for CiweimaoClient {
         Ok(content_infos)
     }
 
-    async fn buy_chapter(&self, info: &ChapterInfo) -> Result<(), Error> {
+    async fn chapter_comments(
+        &self,
+        info: &ChapterInfo,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error> {
+        let response: ChapterCommentsResponse = self
+            .post(
+                "/chapter/get_chapter_comment_list",
+                ChapterCommentsRequest {
+                    chapter_id: info.id,
+                    page,
+                    count: CiweimaoClient::COMMENT_PAGE_SIZE,
+                },
+            )
+            .await?;
+        utils::check_response_success(response.code, response.tip)?;
+
+        let comment_list = response.data.unwrap().comment_list;
+        if comment_list.is_empty() {
+            return Ok(None);
+        }
+
+        let mut result = Vec::with_capacity(comment_list.len());
+        for comment in comment_list {
+            result.push(CiweimaoClient::parse_comment(comment)?);
+        }
+
+        Ok(Some(result))
+    }
+
+    async fn paragraph_comment_counts(
+        &self,
+        info: &ChapterInfo,
+    ) -> Result<std::collections::HashMap<usize, u32>, Error> {
+        let response: TsukkomiNumResponse = self
+            .post(
+                "/chapter/get_tsukkomi_num",
+                TsukkomiNumRequest {
+                    chapter_id: info.id,
+                },
+            )
+            .await?;
+        utils::check_response_success(response.code, response.tip)?;
+
+        // The paragraph index of the server starts from 1 and does not count the images
+        let content_infos = self.content_infos(info).await?;
+
+        let mut result = std::collections::HashMap::new();
+        for item in response.data.unwrap().tsukkomi_num_info {
+            let paragraph_index: usize = item.paragraph_index.parse()?;
+
+            match paragraph_index
+                .checked_sub(1)
+                .and_then(|text_index| crate::to_content_index(&content_infos, text_index))
+            {
+                Some(index) => {
+                    result.insert(index, item.tsukkomi_num.parse()?);
+                }
+                None => error!("The paragraph does not exist: {paragraph_index}"),
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn paragraph_comments(
+        &self,
+        info: &ChapterInfo,
+        index: usize,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error> {
+        let content_infos = self.content_infos(info).await?;
+
+        let response: TsukkomiListResponse = self
+            .post(
+                "/chapter/get_paragraph_tsukkomi_list_new",
+                TsukkomiListRequest {
+                    chapter_id: info.id,
+                    paragraph_index: crate::to_text_index(&content_infos, index)? + 1,
+                    page,
+                    count: CiweimaoClient::COMMENT_PAGE_SIZE,
+                },
+            )
+            .await?;
+        utils::check_response_success(response.code, response.tip)?;
+
+        let tsukkomi_list = response.data.unwrap().tsukkomi_list;
+        if tsukkomi_list.is_empty() {
+            return Ok(None);
+        }
+
+        let mut result = Vec::with_capacity(tsukkomi_list.len());
+        for tsukkomi in tsukkomi_list {
+            result.push(Comment {
+                id: tsukkomi.tsukkomi_id.parse()?,
+                author_name: tsukkomi.reader_info.reader_name.trim().to_string(),
+                author_avatar: tsukkomi.reader_info.avatar_url,
+                content: tsukkomi.tsukkomi_content.trim().to_string(),
+                create_time: tsukkomi.ctime,
+                like_count: Some(tsukkomi.like_num.parse()?),
+                reply_count: None,
+            });
+        }
+
+        Ok(Some(result))
+    }
+
+    async fn novel_reviews(&self, novel_id: u32, page: u16) -> Result<Option<Vec<Review>>, Error> {
+        let response: ReviewListResponse = self
+            .post(
+                "/book/get_review_list",
+                ReviewListRequest {
+                    book_id: novel_id,
+                    page,
+                    count: CiweimaoClient::COMMENT_PAGE_SIZE,
+                },
+            )
+            .await?;
+        utils::check_response_success(response.code, response.tip)?;
+
+        let review_list = response.data.unwrap().review_list;
+        if review_list.is_empty() {
+            return Ok(None);
+        }
+
+        let mut result = Vec::with_capacity(review_list.len());
+        for review in review_list {
+            let title = review.title.trim().to_string();
+
+            result.push(Review {
+                id: review.review_id.parse()?,
+                title: if title.is_empty() { None } else { Some(title) },
+                content: review.content.trim().to_string(),
+                author_name: review.reader_info.reader_name.trim().to_string(),
+                author_avatar: review.reader_info.avatar_url,
+                create_time: review.ctime,
+                like_count: Some(review.like_amount.parse()?),
+                reply_count: Some(review.comment_amount.parse()?),
+            });
+        }
+
+        Ok(Some(result))
+    }
+
+    async fn review_replies(
+        &self,
+        review_id: u32,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error> {
+        let response: ReviewCommentsResponse = self
+            .post(
+                "/book/get_review_comment_list",
+                ReviewCommentsRequest {
+                    review_id,
+                    page,
+                    count: CiweimaoClient::COMMENT_PAGE_SIZE,
+                },
+            )
+            .await?;
+        utils::check_response_success(response.code, response.tip)?;
+
+        let comment_list = response.data.unwrap().review_comment_list;
+        if comment_list.is_empty() {
+            return Ok(None);
+        }
+
+        let mut result = Vec::with_capacity(comment_list.len());
+        for comment in comment_list {
+            result.push(CiweimaoClient::parse_comment(comment)?);
+        }
+
+        Ok(Some(result))
+    }
+
+    async fn reading_progress(&self, novel_id: u32) -> Result<Option<ReadingProgress>, Error> {
+        let response: LastReadChapterResponse = self
+            .post(
+                "/bookshelf/get_last_read_chapter",
+                LastReadChapterRequest { book_id: novel_id },
+            )
+            .await?;
+        utils::check_response_success(response.code, response.tip)?;
+
+        let data = response.data.unwrap();
+        match data.last_read_chapter_id {
+            // 0 未阅读
+            Some(chapter_id) if chapter_id != "0" => Ok(Some(ReadingProgress {
+                chapter_id: chapter_id.parse()?,
+                read_time: data.last_read_time,
+            })),
+            _ => Ok(None),
+        }
+    }
+
+    async fn set_reading_progress(&self, novel_id: u32, chapter_id: u32) -> Result<(), Error> {
         let response: GenericResponse = self
             .post(
-                "/chapter/buy",
-                BuyRequest {
-                    chapter_id: info.id.to_string(),
+                "/bookshelf/set_last_read_chapter",
+                SetLastReadChapterRequest {
+                    book_id: novel_id,
+                    last_read_chapter_id: chapter_id,
                 },
             )
             .await?;
@@ -330,6 +678,51 @@ impl Client for CiweimaoClient {
         Ok(())
     }
 
+    async fn buy_chapter(&self, info: &ChapterInfo) -> Result<(), Error> {
+        self.options
+            .spending
+            .check(self.db().await?, &[info])
+            .await?;
+        if self.options.spending.dry_run(&[info]) {
+            return Ok(());
+        }
+
+        self.do_buy_chapter(info).await
+    }
+
+    async fn buy_chapters(&self, infos: &[ChapterInfo]) -> Result<Vec<ChapterPurchase>, Error> {
+        let chapter_infos = crate::chapters_to_buy(infos);
+        self.check_purchase(&chapter_infos).await?;
+        if self.options.spending.dry_run(&chapter_infos) {
+            return Ok(crate::dry_run_results(infos, &chapter_infos));
+        }
+
+        // There is no batch order, every chapter is bought on its own
+        let mut statuses = std::collections::HashMap::new();
+        for chapter_info in chapter_infos {
+            let result = self.do_buy_chapter(chapter_info).await;
+            crate::record_order(&mut statuses, &[chapter_info.id], result);
+        }
+
+        Ok(crate::purchase_results(infos, statuses))
+    }
+
+    async fn buy_volume(&self, info: &VolumeInfo) -> Result<Vec<ChapterPurchase>, Error> {
+        self.buy_chapters(&info.chapter_infos).await
+    }
+
+    async fn buy_all_remaining(&self, novel_id: u32) -> Result<Vec<ChapterPurchase>, Error> {
+        let Some(volume_infos) = self.volume_infos(novel_id).await? else {
+            return Err(Error::NotFound(format!("novel {novel_id}")));
+        };
+
+        self.buy_chapters(&crate::all_chapters(volume_infos)).await
+    }
+
+    async fn purchase_records(&self) -> Result<Vec<PurchaseRecord>, Error> {
+        self.db().await?.find_purchases().await
+    }
+
     async fn image(&self, url: &Url) -> Result<DynamicImage, Error> {
         match self.db().await?.find_image(url).await? {
             FindImageResult::Ok(image) => Ok(image),
@@ -353,6 +746,10 @@ impl Client for CiweimaoClient {
 
         CATEGORIES
             .get_or_try_init(|| async {
+                if self.options.offline {
+                    return self.db().await?.find_cache(CacheKey::Categories).await;
+                }
+
                 let response: CategoryResponse =
                     self.post("/meta/get_meta_data", EmptyRequest {}).await?;
                 utils::check_response_success(response.code, response.tip)?;
@@ -370,6 +767,11 @@ impl Client for CiweimaoClient {
 
                 result.sort_unstable_by_key(|x| x.id.unwrap());
 
+                self.db()
+                    .await?
+                    .save_cache(CacheKey::Categories, &result)
+                    .await?;
+
                 Ok(result)
             })
             .await
@@ -379,6 +781,10 @@ impl Client for CiweimaoClient {
         static TAGS: OnceCell<Vec<Tag>> = OnceCell::const_new();
 
         TAGS.get_or_try_init(|| async {
+            if self.options.offline {
+                return self.db().await?.find_cache(CacheKey::Tags).await;
+            }
+
             let response: TagResponse = self
                 .post("/book/get_official_tag_list", EmptyRequest {})
                 .await?;
@@ -409,6 +815,8 @@ impl Client for CiweimaoClient {
                 name: String::from("纯百"),
             });
 
+            self.db().await?.save_cache(CacheKey::Tags, &result).await?;
+
             Ok(result)
         })
         .await
@@ -420,6 +828,141 @@ impl Client for CiweimaoClient {
         page: u16,
         size: u16,
     ) -> Result<Option<Vec<u32>>, Error> {
+        Ok(self
+            .do_search(option, page, size)
+            .await?
+            .map(|summaries| summaries.into_iter().map(|summary| summary.id).collect()))
+    }
+
+    async fn search_summaries(
+        &self,
+        option: &Options,
+        page: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        self.do_search(option, page, CiweimaoClient::SEARCH_PAGE_SIZE)
+            .await
+    }
+
+    async fn rankings(
+        &self,
+        kind: RankingKind,
+        period: RankingPeriod,
+        page: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        let time_type = match period {
+            RankingPeriod::Week => "week",
+            RankingPeriod::Month => "month",
+            RankingPeriod::Total => "total",
+        };
+
+        // click 点击榜
+        // month_ticket 月票榜
+        // new_book 新书榜
+        // buy 畅销榜
+        let order = match kind {
+            RankingKind::Popular => "click",
+            RankingKind::Tickets => "month_ticket",
+            RankingKind::NewBooks => "new_book",
+            RankingKind::Bestsellers => "buy",
+        };
+
+        let response: SearchResponse = self
+            .post(
+                "/bookcity/get_rank_book_list",
+                RankRequest {
+                    count: CiweimaoClient::SEARCH_PAGE_SIZE,
+                    page,
+                    order,
+                    time_type,
+                    category_index: 0,
+                },
+            )
+            .await?;
+        utils::check_response_success(response.code, response.tip)?;
+
+        let book_list = response.data.unwrap().book_list;
+        if book_list.is_empty() {
+            return Ok(None);
+        }
+
+        let mut result = Vec::new();
+        for novel_info in book_list {
+            result.push(self.parse_summary(novel_info).await?);
+        }
+
+        Ok(Some(result))
+    }
+}
+
+impl CiweimaoClient {
+    const SEARCH_PAGE_SIZE: u16 = 20;
+    const COMMENT_PAGE_SIZE: u16 = 20;
+
+    /// Check the spending policy and the balance before the chapters are bought
+    async fn check_purchase(&self, chapter_infos: &[&ChapterInfo]) -> Result<(), Error> {
+        if chapter_infos.is_empty() {
+            return Ok(());
+        }
+
+        let spending = &self.options.spending;
+        spending.check(self.db().await?, chapter_infos).await?;
+
+        let result = self
+            .money()
+            .await
+            .and_then(|money| crate::check_balance(chapter_infos, money));
+        if result.is_err() {
+            spending.release(chapter_infos).await;
+        }
+
+        result
+    }
+
+    /// Buy the chapter, the price reserved by the spending policy is released on failure
+    async fn do_buy_chapter(&self, info: &ChapterInfo) -> Result<(), Error> {
+        if let Err(err) = self.post_buy_chapter(info).await {
+            self.options.spending.release(&[info]).await;
+            return Err(err);
+        }
+
+        let db = self.db().await?;
+        // The account has been charged, the purchase must not be reported as failed
+        if let Err(err) = self.options.spending.record(db, &[info]).await {
+            error!("Fail to save the purchase of chapter {}: {err}", info.id);
+        }
+        if let Err(err) = db.set_chapter_paid(info.id).await {
+            error!("Fail to mark chapter {} as paid: {err}", info.id);
+        }
+
+        Ok(())
+    }
+
+    async fn post_buy_chapter(&self, info: &ChapterInfo) -> Result<(), Error> {
+        let response: GenericResponse = self
+            .post(
+                "/chapter/buy",
+                BuyRequest {
+                    chapter_id: info.id.to_string(),
+                },
+            )
+            .await?;
+        utils::check_response_success(response.code, response.tip)
+    }
+
+    async fn do_search(
+        &self,
+        option: &Options,
+        page: u16,
+        size: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        // The server can not filter by author
+        if option.author.is_some() {
+            return Err(Error::Unsupported(format!(
+                "{}: author filter",
+                CiweimaoClient::APP_NAME
+            )));
+        }
+
         let mut category_index = 0;
         if option.category.is_some() {
             category_index = option.category.as_ref().unwrap().id.unwrap();
@@ -481,14 +1024,7 @@ impl Client for CiweimaoClient {
             }
         }
 
-        let order = if option.keyword.is_some() {
-            // When using keyword search, many irrelevant items will appear in the search results
-            // If you use sorting, you will not be able to obtain the target items
-            None
-        } else {
-            // 人气排序
-            Some("week_click")
-        };
+        let order = CiweimaoClient::to_order(option)?;
 
         let response: SearchResponse = self
             .post(
@@ -518,18 +1054,20 @@ impl Client for CiweimaoClient {
         let sys_tags = self.tags().await?;
 
         for novel_info in book_list {
+            let book_info = &novel_info.book_info;
+
             let mut tag_names = Vec::new();
-            for tag in novel_info.tag_list {
+            for tag in &book_info.tag_list {
                 if let Some(sys_tag) = sys_tags.iter().find(|x| x.name == tag.tag_name.trim()) {
                     tag_names.push(sys_tag.name.clone());
                 }
             }
 
-            if CiweimaoClient::match_update_days(option, novel_info.uptime)
+            if CiweimaoClient::match_update_days(option, book_info.uptime)
                 && CiweimaoClient::match_excluded_tags(option, tag_names)
-                && CiweimaoClient::match_word_count(option, novel_info.total_word_count.parse()?)
+                && CiweimaoClient::match_word_count(option, book_info.total_word_count.parse()?)
             {
-                result.push(novel_info.book_id.parse()?);
+                result.push(self.parse_summary(novel_info).await?);
             }
         }
 
@@ -690,18 +1228,13 @@ impl CiweimaoClient {
         })
     }
 
-    async fn shelf_list(&self) -> Result<Vec<u32>, Error> {
+    async fn shelf_list(&self) -> Result<Vec<Shelf>, Error> {
         let response: ShelfListResponse = self
             .post("/bookshelf/get_shelf_list", EmptyRequest {})
             .await?;
         utils::check_response_success(response.code, response.tip)?;
 
-        let mut result = Vec::new();
-        for shelf in response.data.unwrap().shelf_list {
-            result.push(shelf.shelf_id.parse()?);
-        }
-
-        Ok(result)
+        Ok(response.data.unwrap().shelf_list)
     }
 
     async fn chapter_prices(&self, novel_id: u32) -> Result<HashMap<u32, u16>, Error> {
@@ -737,6 +1270,22 @@ impl CiweimaoClient {
         Ok(response.data.unwrap().command)
     }
 
+    fn to_order(option: &Options) -> Result<Option<&'static str>, Error> {
+        match option.sort {
+            // When using keyword search, many irrelevant items will appear in the search results
+            // If you use sorting, you will not be able to obtain the target items
+            None if option.keyword.is_some() => Ok(None),
+            None | Some(SortOrder::Popular) => Ok(Some("week_click")),
+            Some(SortOrder::Latest) => Ok(Some("uptime")),
+            Some(SortOrder::Bookmarks) => Ok(Some("total_favor")),
+            Some(SortOrder::WordCount) => Ok(Some("total_word_count")),
+            Some(sort) => Err(Error::Unsupported(format!(
+                "{}: sort order {sort:?}",
+                CiweimaoClient::APP_NAME
+            ))),
+        }
+    }
+
     fn match_update_days(option: &Options, update_time: NaiveDateTime) -> bool {
         if option.update_days.is_none() {
             return true;
@@ -815,6 +1364,35 @@ impl CiweimaoClient {
         }
     }
 
+    fn parse_comment(comment: CommentInfo) -> Result<Comment, Error> {
+        Ok(Comment {
+            id: comment.comment_id.parse()?,
+            author_name: comment.reader_info.reader_name.trim().to_string(),
+            author_avatar: comment.reader_info.avatar_url,
+            content: comment.comment_content.trim().to_string(),
+            create_time: comment.ctime,
+            like_count: Some(comment.like_num.parse()?),
+            reply_count: Some(comment.reply_num.parse()?),
+        })
+    }
+
+    async fn parse_summary(&self, search_info: SearchInfo) -> Result<NovelSummary, Error> {
+        let book_info = search_info.book_info;
+
+        Ok(NovelSummary {
+            id: search_info.book_id.parse()?,
+            name: book_info.book_name.trim().to_string(),
+            author_name: Some(book_info.author_name.trim().to_string()),
+            cover_url: book_info.cover,
+            word_count: Some(book_info.total_word_count.parse()?),
+            is_vip: Some(book_info.is_paid),
+            is_finished: Some(book_info.up_status),
+            update_time: Some(book_info.uptime),
+            category: self.parse_category(book_info.category_index).await?,
+            tags: self.parse_tags(book_info.tag_list).await?,
+        })
+    }
+
     async fn parse_category<T>(&self, str: T) -> Result<Option<Category>, Error>
     where
         T: AsRef<str>,
@@ -100,6 +100,21 @@ pub(crate) struct BookshelfNovelInfo {
     pub book_id: String,
 }
 
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct ShelfBookRequest {
+    pub shelf_id: u32,
+    pub book_id: u32,
+}
+
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct MoveShelfBookRequest {
+    pub from_shelf_id: u32,
+    pub to_shelf_id: u32,
+    pub book_id: u32,
+}
+
 #[must_use]
 #[derive(Serialize)]
 pub(crate) struct NovelInfoRequest {
@@ -124,6 +139,8 @@ pub(crate) struct NovelInfoData {
 #[derive(Deserialize)]
 pub(crate) struct BookInfo {
     pub book_name: String,
+    // 搜索结果中可能没有 author_id
+    pub author_id: Option<String>,
     pub author_name: String,
     // 有一些小说 cover 为空
     #[serde(with = "crate::ciweimao::parse_url")]
@@ -192,6 +209,196 @@ pub(crate) struct ChapterInfo {
     pub auth_access: bool,
 }
 
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct ChapterCommentsRequest {
+    pub chapter_id: u32,
+    pub page: u16,
+    pub count: u16,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ChapterCommentsResponse {
+    pub code: String,
+    pub tip: Option<String>,
+    pub data: Option<ChapterCommentsData>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ChapterCommentsData {
+    pub comment_list: Vec<CommentInfo>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct CommentInfo {
+    pub comment_id: String,
+    pub comment_content: String,
+    #[serde(with = "crate::common::date_format_option")]
+    pub ctime: Option<NaiveDateTime>,
+    pub like_num: String,
+    pub reply_num: String,
+    pub reader_info: CommentReaderInfo,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct CommentReaderInfo {
+    pub reader_name: String,
+    // 当头像不存在时，是空字符串
+    #[serde(with = "crate::ciweimao::parse_url")]
+    pub avatar_url: Option<Url>,
+}
+
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct ReviewListRequest {
+    pub book_id: u32,
+    pub page: u16,
+    pub count: u16,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ReviewListResponse {
+    pub code: String,
+    pub tip: Option<String>,
+    pub data: Option<ReviewListData>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ReviewListData {
+    pub review_list: Vec<ReviewInfo>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ReviewInfo {
+    pub review_id: String,
+    pub title: String,
+    pub content: String,
+    #[serde(with = "crate::common::date_format_option")]
+    pub ctime: Option<NaiveDateTime>,
+    pub like_amount: String,
+    pub comment_amount: String,
+    pub reader_info: CommentReaderInfo,
+}
+
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct ReviewCommentsRequest {
+    pub review_id: u32,
+    pub page: u16,
+    pub count: u16,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ReviewCommentsResponse {
+    pub code: String,
+    pub tip: Option<String>,
+    pub data: Option<ReviewCommentsData>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ReviewCommentsData {
+    pub review_comment_list: Vec<CommentInfo>,
+}
+
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct TsukkomiNumRequest {
+    pub chapter_id: u32,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct TsukkomiNumResponse {
+    pub code: String,
+    pub tip: Option<String>,
+    pub data: Option<TsukkomiNumData>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct TsukkomiNumData {
+    pub tsukkomi_num_info: Vec<TsukkomiNum>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct TsukkomiNum {
+    pub paragraph_index: String,
+    pub tsukkomi_num: String,
+}
+
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct TsukkomiListRequest {
+    pub chapter_id: u32,
+    pub paragraph_index: usize,
+    pub page: u16,
+    pub count: u16,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct TsukkomiListResponse {
+    pub code: String,
+    pub tip: Option<String>,
+    pub data: Option<TsukkomiListData>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct TsukkomiListData {
+    pub tsukkomi_list: Vec<Tsukkomi>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct Tsukkomi {
+    pub tsukkomi_id: String,
+    pub tsukkomi_content: String,
+    #[serde(with = "crate::common::date_format_option")]
+    pub ctime: Option<NaiveDateTime>,
+    pub like_num: String,
+    pub reader_info: CommentReaderInfo,
+}
+
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct LastReadChapterRequest {
+    pub book_id: u32,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct LastReadChapterResponse {
+    pub code: String,
+    pub tip: Option<String>,
+    pub data: Option<LastReadChapterData>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct LastReadChapterData {
+    pub last_read_chapter_id: Option<String>,
+    #[serde(with = "crate::common::date_format_option")]
+    pub last_read_time: Option<NaiveDateTime>,
+}
+
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct SetLastReadChapterRequest {
+    pub book_id: u32,
+    pub last_read_chapter_id: u32,
+}
+
 #[must_use]
 #[derive(Serialize)]
 pub(crate) struct BuyRequest {
@@ -288,6 +495,48 @@ pub(crate) struct SearchRequest {
     pub filter_word: Option<u8>,
 }
 
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct AuthorRequest {
+    pub author_id: u32,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct AuthorResponse {
+    pub code: String,
+    pub tip: Option<String>,
+    pub data: Option<AuthorData>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct AuthorData {
+    pub author_info: AuthorInfo,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct AuthorInfo {
+    pub author_id: String,
+    pub author_name: String,
+    // 当头像不存在时，是空字符串
+    #[serde(with = "crate::ciweimao::parse_url")]
+    pub avatar_url: Option<Url>,
+    pub author_intro: String,
+    pub fans_num: String,
+}
+
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct RankRequest {
+    pub count: u16,
+    pub page: u16,
+    pub order: &'static str,
+    pub time_type: &'static str,
+    pub category_index: u16,
+}
+
 #[must_use]
 #[derive(Deserialize)]
 pub(crate) struct SearchResponse {
@@ -306,10 +555,8 @@ pub(crate) struct SearchData {
 #[derive(Deserialize)]
 pub(crate) struct SearchInfo {
     pub book_id: String,
-    pub total_word_count: String,
-    #[serde(with = "crate::common::date_format")]
-    pub uptime: NaiveDateTime,
-    pub tag_list: Vec<NovelInfoTag>,
+    #[serde(flatten)]
+    pub book_info: BookInfo,
 }
 
 #[must_use]
@@ -435,6 +682,7 @@ pub(crate) struct ShelfListData {
 #[derive(Deserialize)]
 pub(crate) struct Shelf {
     pub shelf_id: String,
+    pub shelf_name: String,
 }
 
 #[must_use]
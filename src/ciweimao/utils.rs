@@ -15,10 +15,10 @@ use tracing::{error, info};
 use url::{form_urlencoded, Url};
 
 use super::Config;
-use crate::{CiweimaoClient, Error, HTTPClient, NovelDB};
+use crate::{CassetteRules, CiweimaoClient, ClientBuilder, Error, Freshness, HTTPClient, NovelDB};
 
 impl CiweimaoClient {
-    const APP_NAME: &'static str = "ciweimao";
+    pub(crate) const APP_NAME: &'static str = "ciweimao";
     const HOST: &'static str = "https://app.hbooker.com";
 
     pub(crate) const OK: &'static str = "100000";
@@ -40,17 +40,12 @@ impl CiweimaoClient {
 
     /// Create a ciweimao client
     pub async fn new() -> Result<Self, Error> {
-        let config: Option<Config> = crate::load_config_file(CiweimaoClient::APP_NAME)?;
+        Self::builder().build().await
+    }
 
-        Ok(Self {
-            proxy: None,
-            no_proxy: false,
-            cert_path: None,
-            client: OnceCell::new(),
-            client_rss: OnceCell::new(),
-            db: OnceCell::new(),
-            config: RwLock::new(config),
-        })
+    /// Create a builder of ciweimao clients
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::default()
     }
 
     #[must_use]
@@ -92,9 +87,25 @@ impl CiweimaoClient {
         *self.config.write().unwrap() = Some(config);
     }
 
+    /// In offline mode the saved information is always used
+    pub(crate) fn metadata_freshness(&self) -> Freshness {
+        if self.options.offline {
+            Freshness::Forever
+        } else {
+            self.options.freshness
+        }
+    }
+
     pub(crate) async fn db(&self) -> Result<&NovelDB, Error> {
         self.db
-            .get_or_try_init(|| async { NovelDB::new(CiweimaoClient::APP_NAME).await })
+            .get_or_try_init(|| async {
+                NovelDB::new(
+                    CiweimaoClient::APP_NAME,
+                    &self.options.profile,
+                    &self.options.storage,
+                )
+                .await
+            })
             .await
     }
 
@@ -105,9 +116,9 @@ impl CiweimaoClient {
                     .user_agent(CiweimaoClient::USER_AGENT)
                     // 因为 HTTP response body 是加密的，所以压缩是没有意义的
                     .allow_compress(false)
-                    .proxy(self.proxy.clone())
-                    .no_proxy(self.no_proxy)
-                    .cert(self.cert_path.clone())
+                    .options(&self.options)
+                    .rate_limit(self.options.rate_limit)
+                    .cassette_rules(CiweimaoClient::cassette_rules())
                     .build()
                     .await
             })
@@ -119,9 +130,8 @@ impl CiweimaoClient {
             .get_or_try_init(|| async {
                 HTTPClient::builder(CiweimaoClient::APP_NAME)
                     .user_agent(CiweimaoClient::USER_AGENT_RSS)
-                    .proxy(self.proxy.clone())
-                    .no_proxy(self.no_proxy)
-                    .cert(self.cert_path.clone())
+                    .options(&self.options)
+                    .rate_limit(self.options.image_rate_limit)
                     .build()
                     .await
             })
@@ -133,12 +143,10 @@ impl CiweimaoClient {
         T: AsRef<str>,
         E: Serialize,
     {
-        let response = self
-            .client()
-            .await?
-            .get(CiweimaoClient::HOST.to_string() + url.as_ref())
-            .query(&query)
-            .send()
+        let client = self.client().await?;
+
+        let response = client
+            .send(client.get(self.api_url(&url)).query(&query))
             .await?;
         crate::check_status(
             response.status(),
@@ -154,33 +162,14 @@ impl CiweimaoClient {
         E: Serialize,
         R: DeserializeOwned,
     {
-        let mut count = 0;
-
-        let response = loop {
-            let response = self
-                .client()
-                .await?
-                .post(CiweimaoClient::HOST.to_string() + url.as_ref())
-                .form(&self.append_param(&form)?)
-                .send()
-                .await;
-
-            if let Ok(response) = response {
-                break response;
-            } else {
-                info!(
-                    "HTTP request failed: `{}`, retry, number of times: `{}`",
-                    response.as_ref().unwrap_err(),
-                    count + 1
-                );
-
-                count += 1;
-                if count > 3 {
-                    response?;
-                }
-            }
-        };
-
+        let client = self.client().await?;
+        let response = client
+            .send(
+                client
+                    .post(self.api_url(&url))
+                    .form(&self.append_param(&form)?),
+            )
+            .await?;
         crate::check_status(
             response.status(),
             format!("HTTP request failed: `{}`", url.as_ref()),
@@ -194,12 +183,25 @@ impl CiweimaoClient {
     }
 
     pub(crate) async fn get_rss(&self, url: &Url) -> Result<Response, Error> {
-        let response = self.client_rss().await?.get(url.clone()).send().await?;
+        let client = self.client_rss().await?;
+        let response = client.send(client.get(url.clone())).await?;
         crate::check_status(response.status(), format!("HTTP request failed: `{url}`"))?;
 
         Ok(response)
     }
 
+    fn api_url<T>(&self, url: T) -> String
+    where
+        T: AsRef<str>,
+    {
+        let host = match &self.options.host {
+            Some(host) => host.as_str().trim_end_matches('/'),
+            None => CiweimaoClient::HOST,
+        };
+
+        format!("{host}{}", url.as_ref())
+    }
+
     fn append_param<T>(&self, query: T) -> Result<Map<String, Value>, Error>
     where
         T: Serialize,
@@ -229,6 +231,23 @@ impl CiweimaoClient {
         Ok(value.as_object().unwrap().clone())
     }
 
+    /// `rand_str` and `p` change with every request
+    fn cassette_rules() -> CassetteRules {
+        CassetteRules {
+            normalize: |params| {
+                params.remove("rand_str");
+                params.remove("p");
+                Ok(())
+            },
+            decode: |bytes| {
+                let bytes =
+                    crate::aes_256_cbc_no_iv_base64_decrypt(CiweimaoClient::get_aes_key(), bytes)
+                        .ok()?;
+                serde_json::from_slice(&bytes).ok()
+            },
+        }
+    }
+
     #[must_use]
     fn get_aes_key() -> &'static [u8] {
         static AES_KEY: SyncOnceCell<Digest> = SyncOnceCell::new();
@@ -268,6 +287,9 @@ impl CiweimaoClient {
         if self.has_token() {
             crate::save_config_file(
                 CiweimaoClient::APP_NAME,
+                &self.options.profile,
+                &self.options.storage,
+                &self.options.key_source,
                 self.config.write().unwrap().take(),
             )?;
         } else {
@@ -278,6 +300,28 @@ impl CiweimaoClient {
     }
 }
 
+impl ClientBuilder<CiweimaoClient> {
+    /// Check the options and create the client
+    pub async fn build(self) -> Result<CiweimaoClient, Error> {
+        self.options.validate().await?;
+
+        let config: Option<Config> = crate::load_config_file(
+            CiweimaoClient::APP_NAME,
+            &self.options.profile,
+            &self.options.storage,
+            &self.options.key_source,
+        )?;
+
+        Ok(CiweimaoClient {
+            options: self.options,
+            client: OnceCell::new(),
+            client_rss: OnceCell::new(),
+            db: OnceCell::new(),
+            config: RwLock::new(config),
+        })
+    }
+}
+
 impl Drop for CiweimaoClient {
     fn drop(&mut self) {
         if let Err(err) = self.do_shutdown() {
@@ -287,14 +331,13 @@ impl Drop for CiweimaoClient {
 }
 
 pub(crate) fn check_response_success(code: String, tip: Option<String>) -> Result<(), Error> {
-    if code != CiweimaoClient::OK {
-        Err(Error::NovelApi(format!(
-            "{} request failed, code: `{code}`, msg: `{}`",
-            CiweimaoClient::APP_NAME,
-            tip.unwrap().trim()
-        )))
-    } else {
-        Ok(())
+    let msg = tip.unwrap_or_default().trim().to_string();
+
+    match code.as_str() {
+        CiweimaoClient::OK => Ok(()),
+        CiweimaoClient::LOGIN_EXPIRED => Err(Error::LoginExpired),
+        CiweimaoClient::NOT_FOUND => Err(Error::NotFound(msg)),
+        _ => Err(Error::from_server(code, msg)),
     }
 }
 
@@ -1,7 +1,7 @@
 mod structure;
 mod utils;
 
-use std::{io::Cursor, path::PathBuf, sync::RwLock};
+use std::{collections::HashMap, io::Cursor, sync::RwLock};
 
 use chrono::{Duration, Local, NaiveDateTime, TimeZone};
 use chrono_tz::Asia::Shanghai;
@@ -14,9 +14,11 @@ use url::Url;
 
 use self::structure::*;
 use crate::{
-    Category, ChapterInfo, Client, ContentInfo, ContentInfos, Error, FindImageResult,
-    FindTextResult, HTTPClient, NovelDB, NovelInfo, Options, Tag, UserInfo, VolumeInfo,
-    VolumeInfos, WordCountRange,
+    Author, Bookshelf, CacheKey, Category, ChapterInfo, ChapterPurchase, Client, ClientOptions,
+    Comment, ContentInfo, ContentInfos, Error, FindImageResult, FindTextResult, Freshness,
+    HTTPClient, NovelDB, NovelInfo, NovelSummary, Options, PurchaseRecord, RankingKind,
+    RankingPeriod, ReadingProgress, Review, SortOrder, Tag, UserInfo, VolumeInfo, VolumeInfos,
+    WordCountRange,
 };
 
 #[must_use]
@@ -28,9 +30,7 @@ pub(crate) struct Config {
 /// Ciyuanji client, use it to access Apis
 #[must_use]
 pub struct CiyuanjiClient {
-    proxy: Option<Url>,
-    no_proxy: bool,
-    cert_path: Option<PathBuf>,
+    options: ClientOptions,
 
     client: OnceCell<HTTPClient>,
     client_rss: OnceCell<HTTPClient>,
@@ -41,18 +41,6 @@ pub struct CiyuanjiClient {
 }
 
 impl Client for CiyuanjiClient {
-    fn proxy(&mut self, proxy: Url) {
-        self.proxy = Some(proxy);
-    }
-
-    fn no_proxy(&mut self) {
-        self.no_proxy = true;
-    }
-
-    fn cert(&mut self, cert_path: PathBuf) {
-        self.cert_path = Some(cert_path);
-    }
-
     async fn shutdown(&self) -> Result<(), Error> {
         self.client().await?.shutdown()?;
         self.do_shutdown()?;
@@ -121,6 +109,10 @@ impl Client for CiyuanjiClient {
     }
 
     async fn user_info(&self) -> Result<UserInfo, Error> {
+        if self.options.offline {
+            return self.db().await?.find_cache(CacheKey::UserInfo).await;
+        }
+
         let response = self
             .get("/user/getUserInfo")
             .await?
@@ -134,6 +126,11 @@ impl Client for CiyuanjiClient {
             avatar: Some(cm_user.img_url),
         };
 
+        self.db()
+            .await?
+            .save_cache(CacheKey::UserInfo, &user_info)
+            .await?;
+
         Ok(user_info)
     }
 
@@ -165,6 +162,19 @@ impl Client for CiyuanjiClient {
     }
 
     async fn bookshelf_infos(&self) -> Result<Vec<u32>, Error> {
+        Ok(self
+            .bookshelves()
+            .await?
+            .into_iter()
+            .flat_map(|bookshelf| bookshelf.novel_ids)
+            .collect())
+    }
+
+    async fn bookshelves(&self) -> Result<Vec<Bookshelf>, Error> {
+        if self.options.offline {
+            return self.db().await?.find_cache(CacheKey::Bookshelves).await;
+        }
+
         let response = self
             .get_query(
                 "/bookrack/getUserBookRackList",
@@ -181,18 +191,86 @@ impl Client for CiyuanjiClient {
             .await?;
         utils::check_response_success(response.code, response.msg)?;
 
-        let mut result = Vec::new();
-
+        let mut novel_ids = Vec::new();
         for item in response.data.book_rack_list.unwrap() {
-            result.push(item.book_id);
+            novel_ids.push(item.book_id);
         }
 
+        // Ciyuanji has only one bookshelf
+        let result = vec![Bookshelf {
+            id: CiyuanjiClient::BOOKSHELF_ID,
+            name: CiyuanjiClient::BOOKSHELF_NAME.to_string(),
+            novel_ids,
+        }];
+
+        self.db()
+            .await?
+            .save_cache(CacheKey::Bookshelves, &result)
+            .await?;
+
         Ok(result)
     }
 
+    async fn add_to_bookshelf(&self, novel_id: u32, shelf_id: u32) -> Result<(), Error> {
+        CiyuanjiClient::check_shelf_id(shelf_id)?;
+
+        let response = self
+            .post(
+                "/bookrack/addBookRack",
+                AddBookRackRequest {
+                    book_id: novel_id.to_string(),
+                },
+            )
+            .await?
+            .json::<GenericResponse>()
+            .await?;
+        utils::check_response_success(response.code, response.msg)?;
+
+        Ok(())
+    }
+
+    async fn remove_from_bookshelf(&self, novel_id: u32, shelf_id: u32) -> Result<(), Error> {
+        CiyuanjiClient::check_shelf_id(shelf_id)?;
+
+        let response = self
+            .post(
+                "/bookrack/deleteBookRack",
+                DeleteBookRackRequest {
+                    book_ids: novel_id.to_string(),
+                },
+            )
+            .await?
+            .json::<GenericResponse>()
+            .await?;
+        utils::check_response_success(response.code, response.msg)?;
+
+        Ok(())
+    }
+
+    async fn move_between_shelves(
+        &self,
+        _novel_id: u32,
+        _from_shelf_id: u32,
+        _to_shelf_id: u32,
+    ) -> Result<(), Error> {
+        Err(Error::Unsupported(format!(
+            "{}: there is only one bookshelf",
+            CiyuanjiClient::APP_NAME
+        )))
+    }
+
     async fn novel_info(&self, id: u32) -> Result<Option<NovelInfo>, Error> {
         assert!(id > 0);
 
+        if let Some(novel_info) = self
+            .db()
+            .await?
+            .find_novel_info(id, self.metadata_freshness())
+            .await?
+        {
+            return Ok(Some(novel_info));
+        }
+
         let response = self
             .get_query(
                 "/book/getBookDetail",
@@ -239,6 +317,7 @@ impl Client for CiyuanjiClient {
         let novel_info = NovelInfo {
             id,
             name: book.book_name.unwrap().trim().to_string(),
+            author_id: book.author_id,
             author_name: book.author_name.unwrap().trim().to_string(),
             cover_url: book.img_url,
             introduction: CiyuanjiClient::parse_intro(book.notes.unwrap()),
@@ -251,10 +330,68 @@ impl Client for CiyuanjiClient {
             tags: self.parse_tags(book.tag_list.unwrap()).await?,
         };
 
+        self.db().await?.save_novel_info(&novel_info).await?;
+
         Ok(Some(novel_info))
     }
 
+    async fn author_info(&self, id: u32) -> Result<Option<Author>, Error> {
+        let response = self
+            .get_query(
+                "/author/getAuthorInfo",
+                AuthorRequest {
+                    author_id: id.to_string(),
+                },
+            )
+            .await?
+            .json::<AuthorResponse>()
+            .await?;
+        utils::check_response_success(response.code, response.msg)?;
+
+        let Some(author) = response.data.author else {
+            return Ok(None);
+        };
+
+        Ok(Some(Author {
+            id: author.author_id,
+            name: author.author_name.trim().to_string(),
+            avatar: author.img_url,
+            introduction: author.notes.and_then(CiyuanjiClient::parse_intro),
+            follower_count: Some(author.fans_count),
+        }))
+    }
+
+    async fn author_novels(&self, id: u32) -> Result<Vec<NovelSummary>, Error> {
+        let response = self
+            .get_query(
+                "/author/getAuthorBookList",
+                AuthorRequest {
+                    author_id: id.to_string(),
+                },
+            )
+            .await?
+            .json::<BookListResponse>()
+            .await?;
+        utils::check_response_success(response.code, response.msg)?;
+
+        let mut result = Vec::new();
+        for novel_info in response.data.book_list.unwrap_or_default() {
+            result.push(self.parse_summary(novel_info).await?);
+        }
+
+        Ok(result)
+    }
+
     async fn volume_infos(&self, id: u32) -> Result<Option<VolumeInfos>, Error> {
+        if let Some(volume_infos) = self
+            .db()
+            .await?
+            .find_volume_infos(id, self.metadata_freshness())
+            .await?
+        {
+            return Ok(Some(volume_infos));
+        }
+
         let response = self
             .get_query(
                 "/chapter/getChapterListByBookId",
@@ -317,9 +454,25 @@ impl Client for CiyuanjiClient {
             }
         }
 
+        self.db().await?.save_volume_infos(id, &volumes).await?;
+
         Ok(Some(volumes))
     }
 
+    async fn cached_novel_info(&self, id: u32) -> Result<Option<NovelInfo>, Error> {
+        self.db()
+            .await?
+            .find_novel_info(id, Freshness::Forever)
+            .await
+    }
+
+    async fn cached_volume_infos(&self, id: u32) -> Result<Option<VolumeInfos>, Error> {
+        self.db()
+            .await?
+            .find_volume_infos(id, Freshness::Forever)
+            .await
+    }
+
     async fn content_infos(&self, info: &ChapterInfo) -> Result<ContentInfos, Error> {
         let mut content;
 
@@ -327,6 +480,9 @@ impl Client for CiyuanjiClient {
             FindTextResult::Ok(str) => {
                 content = str;
             }
+            FindTextResult::Outdate if self.options.offline => {
+                content = self.db().await?.find_saved_text(info).await?;
+            }
             other => {
                 let response = self
                     .get_query(
@@ -385,18 +541,186 @@ impl Client for CiyuanjiClient {
         Ok(content_infos)
     }
 
-    async fn buy_chapter(&self, info: &ChapterInfo) -> Result<(), Error> {
+    async fn chapter_comments(
+        &self,
+        info: &ChapterInfo,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error> {
         let response = self
-            .post(
-                "/order/consume",
-                BuyChapterRequest {
-                    // always 2
-                    view_type: "2",
-                    // always 1
-                    consume_type: "1",
+            .get_query(
+                "/comment/getChapterCommentList",
+                ChapterCommentsRequest {
                     book_id: info.novel_id.unwrap().to_string(),
-                    product_id: info.id.to_string(),
-                    buy_count: "1",
+                    chapter_id: info.id.to_string(),
+                    page_no: page + 1,
+                    page_size: CiyuanjiClient::COMMENT_PAGE_SIZE,
+                },
+            )
+            .await?
+            .json::<CommentsResponse>()
+            .await?;
+        utils::check_response_success(response.code, response.msg)?;
+
+        Ok(CiyuanjiClient::parse_comments(
+            response.data.comment_list.unwrap_or_default(),
+        ))
+    }
+
+    async fn paragraph_comment_counts(
+        &self,
+        info: &ChapterInfo,
+    ) -> Result<HashMap<usize, u32>, Error> {
+        let response = self
+            .get_query(
+                "/paragraph/getParagraphCommentCount",
+                ParagraphCommentCountsRequest {
+                    book_id: info.novel_id.unwrap().to_string(),
+                    chapter_id: info.id.to_string(),
+                },
+            )
+            .await?
+            .json::<ParagraphCommentCountsResponse>()
+            .await?;
+        utils::check_response_success(response.code, response.msg)?;
+
+        // The paragraph index of the server does not count the images
+        let content_infos = self.content_infos(info).await?;
+
+        let mut result = HashMap::new();
+        for item in response.data.paragraph_list.unwrap_or_default() {
+            match crate::to_content_index(&content_infos, item.paragraph_index) {
+                Some(index) => {
+                    result.insert(index, item.comment_count);
+                }
+                None => error!("The paragraph does not exist: {}", item.paragraph_index),
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn paragraph_comments(
+        &self,
+        info: &ChapterInfo,
+        index: usize,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error> {
+        let content_infos = self.content_infos(info).await?;
+
+        let response = self
+            .get_query(
+                "/paragraph/getParagraphCommentList",
+                ParagraphCommentsRequest {
+                    book_id: info.novel_id.unwrap().to_string(),
+                    chapter_id: info.id.to_string(),
+                    paragraph_index: crate::to_text_index(&content_infos, index)?,
+                    page_no: page + 1,
+                    page_size: CiyuanjiClient::COMMENT_PAGE_SIZE,
+                },
+            )
+            .await?
+            .json::<CommentsResponse>()
+            .await?;
+        utils::check_response_success(response.code, response.msg)?;
+
+        Ok(CiyuanjiClient::parse_comments(
+            response.data.comment_list.unwrap_or_default(),
+        ))
+    }
+
+    async fn novel_reviews(&self, novel_id: u32, page: u16) -> Result<Option<Vec<Review>>, Error> {
+        let response = self
+            .get_query(
+                "/review/getBookReviewList",
+                ReviewListRequest {
+                    book_id: novel_id.to_string(),
+                    page_no: page + 1,
+                    page_size: CiyuanjiClient::COMMENT_PAGE_SIZE,
+                },
+            )
+            .await?
+            .json::<ReviewListResponse>()
+            .await?;
+        utils::check_response_success(response.code, response.msg)?;
+        let review_list = response.data.review_list.unwrap_or_default();
+
+        if review_list.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            review_list
+                .into_iter()
+                .map(|review| Review {
+                    id: review.review_id,
+                    title: review
+                        .title
+                        .map(|title| title.trim().to_string())
+                        .filter(|title| !title.is_empty()),
+                    content: review.content.trim().to_string(),
+                    author_name: review.nick_name.trim().to_string(),
+                    author_avatar: review.img_url,
+                    create_time: review.create_time,
+                    like_count: Some(review.like_count),
+                    reply_count: Some(review.reply_count),
+                })
+                .collect(),
+        ))
+    }
+
+    async fn review_replies(
+        &self,
+        review_id: u32,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error> {
+        let response = self
+            .get_query(
+                "/review/getReviewReplyList",
+                ReviewRepliesRequest {
+                    review_id: review_id.to_string(),
+                    page_no: page + 1,
+                    page_size: CiyuanjiClient::COMMENT_PAGE_SIZE,
+                },
+            )
+            .await?
+            .json::<CommentsResponse>()
+            .await?;
+        utils::check_response_success(response.code, response.msg)?;
+
+        Ok(CiyuanjiClient::parse_comments(
+            response.data.comment_list.unwrap_or_default(),
+        ))
+    }
+
+    async fn reading_progress(&self, novel_id: u32) -> Result<Option<ReadingProgress>, Error> {
+        let response = self
+            .get_query(
+                "/bookrack/getReadRecord",
+                ReadRecordRequest {
+                    book_id: novel_id.to_string(),
+                },
+            )
+            .await?
+            .json::<ReadRecordResponse>()
+            .await?;
+        utils::check_response_success(response.code, response.msg)?;
+
+        Ok(response
+            .data
+            .read_record
+            .map(|read_record| ReadingProgress {
+                chapter_id: read_record.chapter_id,
+                read_time: read_record.read_time,
+            }))
+    }
+
+    async fn set_reading_progress(&self, novel_id: u32, chapter_id: u32) -> Result<(), Error> {
+        let response = self
+            .post(
+                "/bookrack/saveReadRecord",
+                SaveReadRecordRequest {
+                    book_id: novel_id.to_string(),
+                    chapter_id: chapter_id.to_string(),
                 },
             )
             .await?
@@ -407,6 +731,58 @@ impl Client for CiyuanjiClient {
         Ok(())
     }
 
+    async fn buy_chapter(&self, info: &ChapterInfo) -> Result<(), Error> {
+        self.options
+            .spending
+            .check(self.db().await?, &[info])
+            .await?;
+        if self.options.spending.dry_run(&[info]) {
+            return Ok(());
+        }
+
+        self.consume(&[info]).await
+    }
+
+    async fn buy_chapters(&self, infos: &[ChapterInfo]) -> Result<Vec<ChapterPurchase>, Error> {
+        let chapter_infos = crate::chapters_to_buy(infos);
+        self.check_purchase(&chapter_infos).await?;
+        if self.options.spending.dry_run(&chapter_infos) {
+            return Ok(crate::dry_run_results(infos, &chapter_infos));
+        }
+
+        let runs = match self.consecutive_chapters(chapter_infos.clone()).await {
+            Ok(runs) => runs,
+            Err(err) => {
+                self.options.spending.release(&chapter_infos).await;
+                return Err(err);
+            }
+        };
+
+        let mut statuses = HashMap::new();
+        for chapter_infos in runs {
+            let result = self.consume(&chapter_infos).await;
+            crate::record_order(&mut statuses, &crate::chapter_ids(&chapter_infos), result);
+        }
+
+        Ok(crate::purchase_results(infos, statuses))
+    }
+
+    async fn buy_volume(&self, info: &VolumeInfo) -> Result<Vec<ChapterPurchase>, Error> {
+        self.buy_chapters(&info.chapter_infos).await
+    }
+
+    async fn buy_all_remaining(&self, novel_id: u32) -> Result<Vec<ChapterPurchase>, Error> {
+        let Some(volume_infos) = self.volume_infos(novel_id).await? else {
+            return Err(Error::NotFound(format!("novel {novel_id}")));
+        };
+
+        self.buy_chapters(&crate::all_chapters(volume_infos)).await
+    }
+
+    async fn purchase_records(&self) -> Result<Vec<PurchaseRecord>, Error> {
+        self.db().await?.find_purchases().await
+    }
+
     async fn image(&self, url: &Url) -> Result<DynamicImage, Error> {
         match self.db().await?.find_image(url).await? {
             FindImageResult::Ok(image) => Ok(image),
@@ -430,6 +806,10 @@ impl Client for CiyuanjiClient {
 
         CATEGORIES
             .get_or_try_init(|| async {
+                if self.options.offline {
+                    return self.db().await?.find_cache(CacheKey::Categories).await;
+                }
+
                 let mut result = Vec::with_capacity(32);
 
                 // 1 男生
@@ -440,7 +820,13 @@ impl Client for CiyuanjiClient {
 
                 result.sort_unstable_by_key(|x| x.id.unwrap());
 
-                Ok(result.into_iter().dedup().collect_vec())
+                let result = result.into_iter().dedup().collect_vec();
+                self.db()
+                    .await?
+                    .save_cache(CacheKey::Categories, &result)
+                    .await?;
+
+                Ok(result)
             })
             .await
     }
@@ -449,6 +835,10 @@ impl Client for CiyuanjiClient {
         static TAGS: OnceCell<Vec<Tag>> = OnceCell::const_new();
 
         TAGS.get_or_try_init(|| async {
+            if self.options.offline {
+                return self.db().await?.find_cache(CacheKey::Tags).await;
+            }
+
             let mut result = Vec::with_capacity(64);
 
             self.get_tags(1, &mut result).await?;
@@ -501,7 +891,10 @@ impl Client for CiyuanjiClient {
 
             result.sort_unstable_by_key(|x| x.id.unwrap());
 
-            Ok(result.into_iter().dedup().collect_vec())
+            let result = result.into_iter().dedup().collect_vec();
+            self.db().await?.save_cache(CacheKey::Tags, &result).await?;
+
+            Ok(result)
         })
         .await
     }
@@ -512,21 +905,227 @@ impl Client for CiyuanjiClient {
         page: u16,
         size: u16,
     ) -> Result<Option<Vec<u32>>, Error> {
+        Ok(self
+            .do_search(option, page, size)
+            .await?
+            .map(|summaries| summaries.into_iter().map(|summary| summary.id).collect()))
+    }
+
+    async fn search_summaries(
+        &self,
+        option: &Options,
+        page: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        self.do_search(option, page, CiyuanjiClient::SEARCH_PAGE_SIZE)
+            .await
+    }
+
+    async fn rankings(
+        &self,
+        kind: RankingKind,
+        period: RankingPeriod,
+        page: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        // 1 周榜
+        // 2 月榜
+        // 3 总榜
+        let time_type = match period {
+            RankingPeriod::Week => "1",
+            RankingPeriod::Month => "2",
+            RankingPeriod::Total => "3",
+        };
+
+        // 1 人气榜
+        // 2 订阅榜
+        // 3 月票榜
+        // 4 新书榜
+        let rank_type = match kind {
+            RankingKind::Popular => "1",
+            RankingKind::Bestsellers => "2",
+            RankingKind::Tickets => "3",
+            RankingKind::NewBooks => "4",
+        };
+
+        let response = self
+            .get_query(
+                "/rank/getRankBookList",
+                RankBookListRequest {
+                    page_no: page + 1,
+                    page_size: CiyuanjiClient::SEARCH_PAGE_SIZE,
+                    rank_type,
+                    time_type,
+                },
+            )
+            .await?
+            .json::<BookListResponse>()
+            .await?;
+        utils::check_response_success(response.code, response.msg)?;
+        let book_list = response.data.book_list.unwrap();
+
+        if book_list.is_empty() {
+            return Ok(None);
+        }
+
+        let mut result = Vec::new();
+        for novel_info in book_list {
+            result.push(self.parse_summary(novel_info).await?);
+        }
+
+        Ok(Some(result))
+    }
+}
+
+impl CiyuanjiClient {
+    const SEARCH_PAGE_SIZE: u16 = 20;
+    const COMMENT_PAGE_SIZE: u16 = 20;
+
+    const BOOKSHELF_ID: u32 = 1;
+    const BOOKSHELF_NAME: &'static str = "书架";
+
+    fn check_shelf_id(shelf_id: u32) -> Result<(), Error> {
+        if shelf_id == CiyuanjiClient::BOOKSHELF_ID {
+            Ok(())
+        } else {
+            Err(Error::NotFound(format!("bookshelf {shelf_id}")))
+        }
+    }
+
+    /// Check the spending policy and the balance before the chapters are bought
+    async fn check_purchase(&self, chapter_infos: &[&ChapterInfo]) -> Result<(), Error> {
+        if chapter_infos.is_empty() {
+            return Ok(());
+        }
+
+        let spending = &self.options.spending;
+        spending.check(self.db().await?, chapter_infos).await?;
+
+        let result = self
+            .money()
+            .await
+            .and_then(|money| crate::check_balance(chapter_infos, money));
+        if result.is_err() {
+            spending.release(chapter_infos).await;
+        }
+
+        result
+    }
+
+    /// Buy the consecutive chapters with one order, starting from the first one,
+    /// the price reserved by the spending policy is released on failure
+    async fn consume(&self, chapter_infos: &[&ChapterInfo]) -> Result<(), Error> {
+        if let Err(err) = self.post_consume(chapter_infos).await {
+            self.options.spending.release(chapter_infos).await;
+            return Err(err);
+        }
+
+        let db = self.db().await?;
+        // The account has been charged, the purchase must not be reported as failed
+        if let Err(err) = self.options.spending.record(db, chapter_infos).await {
+            error!(
+                "Fail to save the purchase of chapters {:?}: {err}",
+                crate::chapter_ids(chapter_infos)
+            );
+        }
+        for chapter_info in chapter_infos {
+            if let Err(err) = db.set_chapter_paid(chapter_info.id).await {
+                error!("Fail to mark chapter {} as paid: {err}", chapter_info.id);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn post_consume(&self, chapter_infos: &[&ChapterInfo]) -> Result<(), Error> {
+        let first = chapter_infos[0];
+
+        let response = self
+            .post(
+                "/order/consume",
+                BuyChapterRequest {
+                    // always 2
+                    view_type: "2",
+                    // always 1
+                    consume_type: "1",
+                    book_id: first.novel_id.unwrap().to_string(),
+                    product_id: first.id.to_string(),
+                    buy_count: chapter_infos.len().to_string(),
+                },
+            )
+            .await?
+            .json::<GenericResponse>()
+            .await?;
+        utils::check_response_success(response.code, response.msg)
+    }
+
+    /// Split the chapters into runs of chapters that are next to each other in the same novel,
+    /// so that each run can be bought with one order
+    async fn consecutive_chapters<'a>(
+        &self,
+        chapter_infos: Vec<&'a ChapterInfo>,
+    ) -> Result<Vec<Vec<&'a ChapterInfo>>, Error> {
+        let novel_ids = chapter_infos
+            .iter()
+            .filter_map(|chapter_info| chapter_info.novel_id)
+            .unique()
+            .collect::<Vec<_>>();
+
+        let mut positions = HashMap::new();
+        for novel_id in novel_ids {
+            if let Some(volume_infos) = self.volume_infos(novel_id).await? {
+                for (position, chapter_info) in crate::all_chapters(volume_infos).iter().enumerate()
+                {
+                    positions.insert(chapter_info.id, (novel_id, position));
+                }
+            }
+        }
+
+        let mut result: Vec<Vec<&ChapterInfo>> = Vec::new();
+        let mut last_position = None;
+        for chapter_info in chapter_infos {
+            let position = positions.get(&chapter_info.id).copied();
+
+            match (last_position, position) {
+                (Some((last_novel_id, last)), Some((novel_id, current)))
+                    if last_novel_id == novel_id && last + 1 == current =>
+                {
+                    result.last_mut().unwrap().push(chapter_info);
+                }
+                _ => result.push(vec![chapter_info]),
+            }
+
+            last_position = position;
+        }
+
+        Ok(result)
+    }
+
+    async fn do_search(
+        &self,
+        option: &Options,
+        page: u16,
+        size: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        // The server can not filter by author
+        if option.author.is_some() {
+            return Err(Error::Unsupported(format!(
+                "{}: author filter",
+                CiyuanjiClient::APP_NAME
+            )));
+        }
+
         if option.keyword.is_some() {
             self.do_search_with_keyword(option, page, size).await
         } else {
             self.do_search_without_keyword(option, page, size).await
         }
     }
-}
 
-impl CiyuanjiClient {
     async fn do_search_with_keyword(
         &self,
         option: &Options,
         page: u16,
         size: u16,
-    ) -> Result<Option<Vec<u32>>, Error> {
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
         let (start_word, end_word) = CiyuanjiClient::to_word(option);
         let (first_classify, _) = CiyuanjiClient::to_classify_ids(option);
 
@@ -536,11 +1135,7 @@ impl CiyuanjiClient {
                 SearchBookListRequest {
                     page_no: page + 1,
                     page_size: size,
-                    // 0 按推荐
-                    // 1 按人气
-                    // 2 按销量
-                    // 3 按更新
-                    rank_type: "1",
+                    rank_type: CiyuanjiClient::to_rank_type(option)?,
                     keyword: option.keyword.as_ref().unwrap().to_string(),
                     is_fee: CiyuanjiClient::to_is_fee(option),
                     end_state: CiyuanjiClient::to_end_state(option),
@@ -590,7 +1185,30 @@ impl CiyuanjiClient {
                     novel_info.second_classify,
                 )
             {
-                result.push(novel_info.book_id);
+                let tag_list = tag_ids
+                    .iter()
+                    .filter_map(|id| sys_tags.iter().find(|tag| tag.id == Some(*id)))
+                    .map(|tag| BookTag {
+                        tag_id: tag.id.unwrap(),
+                        tag_name: tag.name.clone(),
+                    })
+                    .collect_vec();
+
+                // Shaped like the book list, so that both searches return the same summary
+                let novel_info = BookListBook {
+                    book_id: novel_info.book_id,
+                    book_name: novel_info.book_name,
+                    author_name: novel_info.author_name,
+                    img_url: novel_info.img_url,
+                    word_count: novel_info.word_count,
+                    end_state: novel_info.end_state,
+                    is_vip: novel_info.is_vip,
+                    first_classify: novel_info.first_classify,
+                    second_classify: novel_info.second_classify,
+                    latest_update_time: novel_info.latest_update_time,
+                    tag_list: Some(tag_list),
+                };
+                result.push(self.parse_summary(novel_info).await?);
             }
         }
 
@@ -602,7 +1220,7 @@ impl CiyuanjiClient {
         option: &Options,
         page: u16,
         size: u16,
-    ) -> Result<Option<Vec<u32>>, Error> {
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
         let (start_word, end_word) = CiyuanjiClient::to_word(option);
         let (first_classify, second_classify) = CiyuanjiClient::to_classify_ids(option);
 
@@ -612,12 +1230,7 @@ impl CiyuanjiClient {
                 BookListRequest {
                     page_no: page + 1,
                     page_size: size,
-                    // 1 人气最高
-                    // 2 订阅最多
-                    // 3 最近更新
-                    // 4 最近上架
-                    // 6 最近新书
-                    rank_type: "1",
+                    rank_type: CiyuanjiClient::to_rank_type(option)?,
                     first_classify,
                     second_classify,
                     start_word,
@@ -638,24 +1251,80 @@ impl CiyuanjiClient {
 
         let mut result = Vec::new();
         for novel_info in book_list {
-            let mut tag_ids = Vec::new();
-            if novel_info.tag_list.is_some() {
-                for tags in novel_info.tag_list.unwrap() {
-                    tag_ids.push(tags.tag_id);
-                }
-            }
+            let tag_ids = novel_info
+                .tag_list
+                .iter()
+                .flatten()
+                .map(|tag| tag.tag_id)
+                .collect_vec();
 
             if CiyuanjiClient::match_update_days(option, novel_info.latest_update_time)
                 && CiyuanjiClient::match_tags(option, &tag_ids)
                 && CiyuanjiClient::match_excluded_tags(option, &tag_ids)
             {
-                result.push(novel_info.book_id);
+                result.push(self.parse_summary(novel_info).await?);
             }
         }
 
         Ok(Some(result))
     }
 
+    fn parse_comments(comments: Vec<CommentData>) -> Option<Vec<Comment>> {
+        if comments.is_empty() {
+            return None;
+        }
+
+        Some(
+            comments
+                .into_iter()
+                .map(|comment| Comment {
+                    id: comment.comment_id,
+                    author_name: comment.nick_name.trim().to_string(),
+                    author_avatar: comment.img_url,
+                    content: comment.content.trim().to_string(),
+                    create_time: comment.create_time,
+                    like_count: Some(comment.like_count),
+                    reply_count: Some(comment.reply_count),
+                })
+                .collect(),
+        )
+    }
+
+    async fn parse_summary(&self, novel_info: BookListBook) -> Result<NovelSummary, Error> {
+        Ok(NovelSummary {
+            id: novel_info.book_id,
+            name: novel_info.book_name.trim().to_string(),
+            author_name: Some(novel_info.author_name.trim().to_string()),
+            cover_url: novel_info.img_url,
+            word_count: CiyuanjiClient::parse_word_count(novel_info.word_count),
+            is_vip: Some(novel_info.is_vip == "1"),
+            is_finished: Some(novel_info.end_state == "1"),
+            update_time: novel_info.latest_update_time,
+            category: self
+                .find_category(novel_info.first_classify, novel_info.second_classify)
+                .await?,
+            tags: self
+                .parse_tags(novel_info.tag_list.unwrap_or_default())
+                .await?,
+        })
+    }
+
+    fn to_rank_type(option: &Options) -> Result<&'static str, Error> {
+        // Both the search and the book list use
+        // 1 人气最高
+        // 2 订阅最多
+        // 3 最近更新
+        match option.sort {
+            None | Some(SortOrder::Popular) => Ok("1"),
+            Some(SortOrder::Sales) => Ok("2"),
+            Some(SortOrder::Latest) => Ok("3"),
+            Some(sort) => Err(Error::Unsupported(format!(
+                "{}: sort order {sort:?}",
+                CiyuanjiClient::APP_NAME
+            ))),
+        }
+    }
+
     fn to_end_state(option: &Options) -> Option<String> {
         option.is_finished.map(|x| {
             if x {
@@ -771,6 +1440,23 @@ impl CiyuanjiClient {
         })
     }
 
+    async fn find_category(
+        &self,
+        first_classify: Option<u16>,
+        second_classify: Option<u16>,
+    ) -> Result<Option<Category>, Error> {
+        let Some(id) = second_classify.or(first_classify) else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .categories()
+            .await?
+            .iter()
+            .find(|category| category.id == Some(id))
+            .cloned())
+    }
+
     fn parse_word_count(word_count: i32) -> Option<u32> {
         // Some novels have negative word counts, e.g. 9326
         if word_count <= 0 {
@@ -130,6 +130,20 @@ pub(crate) struct BookRack {
     pub book_id: u32,
 }
 
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AddBookRackRequest {
+    pub book_id: String,
+}
+
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DeleteBookRackRequest {
+    pub book_ids: String,
+}
+
 #[must_use]
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -158,6 +172,7 @@ pub(crate) struct BookDetail {
     pub book_id: u32,
     pub book_name: Option<String>,
     pub img_url: Option<Url>,
+    pub author_id: Option<u32>,
     pub author_name: Option<String>,
     pub word_count: i32,
     pub first_classify: Option<u16>,
@@ -268,6 +283,174 @@ pub(crate) struct ContentImage {
     pub paragraph_index: usize,
 }
 
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChapterCommentsRequest {
+    pub book_id: String,
+    pub chapter_id: String,
+    pub page_no: u16,
+    pub page_size: u16,
+}
+
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParagraphCommentCountsRequest {
+    pub book_id: String,
+    pub chapter_id: String,
+}
+
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParagraphCommentsRequest {
+    pub book_id: String,
+    pub chapter_id: String,
+    pub paragraph_index: usize,
+    pub page_no: u16,
+    pub page_size: u16,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct CommentsResponse {
+    pub code: String,
+    pub msg: String,
+    pub data: CommentsData,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CommentsData {
+    pub comment_list: Option<Vec<CommentData>>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CommentData {
+    pub comment_id: u32,
+    pub content: String,
+    pub nick_name: String,
+    pub img_url: Option<Url>,
+    #[serde(with = "crate::common::date_format_option")]
+    pub create_time: Option<NaiveDateTime>,
+    pub like_count: u32,
+    pub reply_count: u32,
+}
+
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReviewListRequest {
+    pub book_id: String,
+    pub page_no: u16,
+    pub page_size: u16,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ReviewListResponse {
+    pub code: String,
+    pub msg: String,
+    pub data: ReviewListData,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReviewListData {
+    pub review_list: Option<Vec<ReviewData>>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReviewData {
+    pub review_id: u32,
+    pub title: Option<String>,
+    pub content: String,
+    pub nick_name: String,
+    pub img_url: Option<Url>,
+    #[serde(with = "crate::common::date_format_option")]
+    pub create_time: Option<NaiveDateTime>,
+    pub like_count: u32,
+    pub reply_count: u32,
+}
+
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReviewRepliesRequest {
+    pub review_id: String,
+    pub page_no: u16,
+    pub page_size: u16,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ParagraphCommentCountsResponse {
+    pub code: String,
+    pub msg: String,
+    pub data: ParagraphCommentCountsData,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParagraphCommentCountsData {
+    pub paragraph_list: Option<Vec<ParagraphCommentCount>>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParagraphCommentCount {
+    pub paragraph_index: usize,
+    pub comment_count: u32,
+}
+
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReadRecordRequest {
+    pub book_id: String,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ReadRecordResponse {
+    pub code: String,
+    pub msg: String,
+    pub data: ReadRecordData,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReadRecordData {
+    pub read_record: Option<ReadRecord>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReadRecord {
+    pub chapter_id: u32,
+    #[serde(with = "crate::common::date_format_option")]
+    pub read_time: Option<NaiveDateTime>,
+}
+
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SaveReadRecordRequest {
+    pub book_id: String,
+    pub chapter_id: String,
+}
+
 #[must_use]
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -276,7 +459,7 @@ pub(crate) struct BuyChapterRequest {
     pub consume_type: &'static str,
     pub book_id: String,
     pub product_id: String,
-    pub buy_count: &'static str,
+    pub buy_count: String,
 }
 
 #[must_use]
@@ -387,6 +570,12 @@ pub(crate) struct SearchData {
 #[serde(rename_all = "camelCase")]
 pub(crate) struct SearchBook {
     pub book_id: u32,
+    pub book_name: String,
+    pub author_name: String,
+    pub img_url: Option<Url>,
+    pub word_count: i32,
+    pub end_state: String,
+    pub is_vip: String,
     pub first_classify: Option<u16>,
     pub second_classify: Option<u16>,
     pub tag_name: Option<String>,
@@ -410,6 +599,48 @@ pub(crate) struct BookListRequest {
     pub is_fee: Option<String>,
 }
 
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuthorRequest {
+    pub author_id: String,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct AuthorResponse {
+    pub code: String,
+    pub msg: String,
+    pub data: AuthorData,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct AuthorData {
+    pub author: Option<AuthorDetail>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuthorDetail {
+    pub author_id: u32,
+    pub author_name: String,
+    pub img_url: Option<Url>,
+    pub notes: Option<String>,
+    pub fans_count: u32,
+}
+
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RankBookListRequest {
+    pub page_no: u16,
+    pub page_size: u16,
+    pub rank_type: &'static str,
+    pub time_type: &'static str,
+}
+
 #[must_use]
 #[derive(Deserialize)]
 pub(crate) struct BookListResponse {
@@ -430,6 +661,14 @@ pub(crate) struct BookListData {
 #[serde(rename_all = "camelCase")]
 pub(crate) struct BookListBook {
     pub book_id: u32,
+    pub book_name: String,
+    pub author_name: String,
+    pub img_url: Option<Url>,
+    pub word_count: i32,
+    pub end_state: String,
+    pub is_vip: String,
+    pub first_classify: Option<u16>,
+    pub second_classify: Option<u16>,
     #[serde(with = "crate::common::date_format_option")]
     pub latest_update_time: Option<NaiveDateTime>,
     pub tag_list: Option<Vec<BookTag>>,
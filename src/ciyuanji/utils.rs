@@ -6,22 +6,24 @@ use std::{
 use hex_simd::AsciiCase;
 use reqwest::{header::HeaderValue, Response};
 use serde::Serialize;
-use serde_json::json;
+use serde_json::{json, Value};
 use tokio::sync::OnceCell;
 use tracing::{error, info};
 use url::Url;
 use uuid::Uuid;
 
 use super::Config;
-use crate::{CiyuanjiClient, Error, HTTPClient, NovelDB};
+use crate::{CassetteRules, CiyuanjiClient, ClientBuilder, Error, Freshness, HTTPClient, NovelDB};
 
 impl CiyuanjiClient {
-    const APP_NAME: &'static str = "ciyuanji";
+    pub(crate) const APP_NAME: &'static str = "ciyuanji";
     const HOST: &'static str = "https://api.hwnovel.com/api/ciyuanji/client";
 
     pub(crate) const OK: &'static str = "200";
     pub(crate) const FAILED: &'static str = "400";
     pub(crate) const ALREADY_SIGNED_IN_MSG: &'static str = "今日已签到";
+    pub(crate) const LOGIN_EXPIRED_MSGS: [&'static str; 3] = ["请先登录", "登录已过期", "登录失效"];
+    pub(crate) const NOT_FOUND_MSG: &'static str = "不存在";
 
     const VERSION: &'static str = "3.4.2";
     const PLATFORM: &'static str = "1";
@@ -35,17 +37,12 @@ impl CiyuanjiClient {
 
     /// Create a ciyuanji client
     pub async fn new() -> Result<Self, Error> {
-        let config: Option<Config> = crate::load_config_file(CiyuanjiClient::APP_NAME)?;
+        Self::builder().build().await
+    }
 
-        Ok(Self {
-            proxy: None,
-            no_proxy: false,
-            cert_path: None,
-            client: OnceCell::new(),
-            client_rss: OnceCell::new(),
-            db: OnceCell::new(),
-            config: RwLock::new(config),
-        })
+    /// Create a builder of ciyuanji clients
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::default()
     }
 
     #[must_use]
@@ -72,9 +69,25 @@ impl CiyuanjiClient {
         *self.config.write().unwrap() = Some(config);
     }
 
+    /// In offline mode the saved information is always used
+    pub(crate) fn metadata_freshness(&self) -> Freshness {
+        if self.options.offline {
+            Freshness::Forever
+        } else {
+            self.options.freshness
+        }
+    }
+
     pub(crate) async fn db(&self) -> Result<&NovelDB, Error> {
         self.db
-            .get_or_try_init(|| async { NovelDB::new(CiyuanjiClient::APP_NAME).await })
+            .get_or_try_init(|| async {
+                NovelDB::new(
+                    CiyuanjiClient::APP_NAME,
+                    &self.options.profile,
+                    &self.options.storage,
+                )
+                .await
+            })
             .await
     }
 
@@ -88,9 +101,9 @@ impl CiyuanjiClient {
                         HeaderValue::from_static(CiyuanjiClient::PLATFORM),
                     )
                     .user_agent(CiyuanjiClient::USER_AGENT)
-                    .proxy(self.proxy.clone())
-                    .no_proxy(self.no_proxy)
-                    .cert(self.cert_path.clone())
+                    .options(&self.options)
+                    .rate_limit(self.options.rate_limit)
+                    .cassette_rules(CiyuanjiClient::cassette_rules())
                     .build()
                     .await
             })
@@ -102,9 +115,8 @@ impl CiyuanjiClient {
             .get_or_try_init(|| async {
                 HTTPClient::builder(CiyuanjiClient::APP_NAME)
                     .user_agent(CiyuanjiClient::USER_AGENT_RSS)
-                    .proxy(self.proxy.clone())
-                    .no_proxy(self.no_proxy)
-                    .cert(self.cert_path.clone())
+                    .options(&self.options)
+                    .rate_limit(self.options.image_rate_limit)
                     .build()
                     .await
             })
@@ -115,13 +127,15 @@ impl CiyuanjiClient {
     where
         T: AsRef<str>,
     {
-        let response = self
-            .client()
-            .await?
-            .get(CiyuanjiClient::HOST.to_string() + url.as_ref())
-            .query(&GenericRequest::new(json!({}))?)
-            .header("token", self.try_token())
-            .send()
+        let client = self.client().await?;
+
+        let response = client
+            .send(
+                client
+                    .get(self.api_url(&url))
+                    .query(&GenericRequest::new(json!({}))?)
+                    .header("token", self.try_token()),
+            )
             .await?;
         crate::check_status(
             response.status(),
@@ -136,34 +150,15 @@ impl CiyuanjiClient {
         T: AsRef<str>,
         E: Serialize,
     {
-        let mut count = 0;
-
-        let response = loop {
-            let response = self
-                .client()
-                .await?
-                .get(CiyuanjiClient::HOST.to_string() + url.as_ref())
-                .query(&GenericRequest::new(&query)?)
-                .header("token", self.try_token())
-                .send()
-                .await;
-
-            if let Ok(response) = response {
-                break response;
-            } else {
-                info!(
-                    "HTTP request failed: `{}`, retry, number of times: `{}`",
-                    response.as_ref().unwrap_err(),
-                    count + 1
-                );
-
-                count += 1;
-                if count > 3 {
-                    response?;
-                }
-            }
-        };
-
+        let client = self.client().await?;
+        let response = client
+            .send(
+                client
+                    .get(self.api_url(&url))
+                    .query(&GenericRequest::new(&query)?)
+                    .header("token", self.try_token()),
+            )
+            .await?;
         crate::check_status(
             response.status(),
             format!("HTTP request failed: `{}`", url.as_ref()),
@@ -177,13 +172,15 @@ impl CiyuanjiClient {
         T: AsRef<str>,
         E: Serialize,
     {
-        let response = self
-            .client()
-            .await?
-            .post(CiyuanjiClient::HOST.to_string() + url.as_ref())
-            .json(&GenericRequest::new(json)?)
-            .header("token", self.try_token())
-            .send()
+        let client = self.client().await?;
+
+        let response = client
+            .send(
+                client
+                    .post(self.api_url(&url))
+                    .json(&GenericRequest::new(json)?)
+                    .header("token", self.try_token()),
+            )
             .await?;
         crate::check_status(
             response.status(),
@@ -194,16 +191,59 @@ impl CiyuanjiClient {
     }
 
     pub(crate) async fn get_rss(&self, url: &Url) -> Result<Response, Error> {
-        let response = self.client_rss().await?.get(url.clone()).send().await?;
+        let client = self.client_rss().await?;
+        let response = client.send(client.get(url.clone())).await?;
         crate::check_status(response.status(), format!("HTTP request failed: `{url}`"))?;
 
         Ok(response)
     }
 
+    /// `requestId`, `sign` and `timestamp` change with every request,
+    /// `param` is encrypted and contains the timestamp too
+    fn cassette_rules() -> CassetteRules {
+        CassetteRules {
+            normalize: |params| {
+                for key in ["requestId", "sign", "timestamp"] {
+                    params.remove(key);
+                }
+
+                if let Some(param) = params.get_mut("param") {
+                    let mut json: Value = serde_json::from_str(&crate::des_ecb_base64_decrypt(
+                        CiyuanjiClient::DES_KEY,
+                        &param,
+                    )?)?;
+                    if let Some(object) = json.as_object_mut() {
+                        object.remove("timestamp");
+                    }
+
+                    *param = json.to_string();
+                }
+
+                Ok(())
+            },
+            ..Default::default()
+        }
+    }
+
+    fn api_url<T>(&self, url: T) -> String
+    where
+        T: AsRef<str>,
+    {
+        let host = match &self.options.host {
+            Some(host) => host.as_str().trim_end_matches('/'),
+            None => CiyuanjiClient::HOST,
+        };
+
+        format!("{host}{}", url.as_ref())
+    }
+
     pub(crate) fn do_shutdown(&self) -> Result<(), Error> {
         if self.has_token() {
             crate::save_config_file(
                 CiyuanjiClient::APP_NAME,
+                &self.options.profile,
+                &self.options.storage,
+                &self.options.key_source,
                 self.config.write().unwrap().take(),
             )?;
         } else {
@@ -214,6 +254,28 @@ impl CiyuanjiClient {
     }
 }
 
+impl ClientBuilder<CiyuanjiClient> {
+    /// Check the options and create the client
+    pub async fn build(self) -> Result<CiyuanjiClient, Error> {
+        self.options.validate().await?;
+
+        let config: Option<Config> = crate::load_config_file(
+            CiyuanjiClient::APP_NAME,
+            &self.options.profile,
+            &self.options.storage,
+            &self.options.key_source,
+        )?;
+
+        Ok(CiyuanjiClient {
+            options: self.options,
+            client: OnceCell::new(),
+            client_rss: OnceCell::new(),
+            db: OnceCell::new(),
+            config: RwLock::new(config),
+        })
+    }
+}
+
 impl Drop for CiyuanjiClient {
     fn drop(&mut self) {
         if let Err(err) = self.do_shutdown() {
@@ -266,14 +328,21 @@ impl GenericRequest {
 }
 
 pub(crate) fn check_response_success(code: String, msg: String) -> Result<(), Error> {
-    if code != CiyuanjiClient::OK {
-        Err(Error::NovelApi(format!(
-            "{} request failed, code: `{code}`, msg: `{}`",
-            CiyuanjiClient::APP_NAME,
-            msg.trim()
-        )))
+    if code == CiyuanjiClient::OK {
+        return Ok(());
+    }
+
+    // Ciyuanji reports every failure with the same code, only the message tells them apart
+    let msg = msg.trim().to_string();
+    if CiyuanjiClient::LOGIN_EXPIRED_MSGS
+        .iter()
+        .any(|pattern| msg.contains(pattern))
+    {
+        Err(Error::LoginExpired)
+    } else if msg.contains(CiyuanjiClient::NOT_FOUND_MSG) {
+        Err(Error::NotFound(msg))
     } else {
-        Ok(())
+        Err(Error::from_server(code, msg))
     }
 }
 
@@ -6,35 +6,24 @@ use aes::{
 };
 use cbc::Decryptor;
 use ring::{
-    aead::{
-        Aad, BoundKey, Nonce, NonceSequence, OpeningKey, SealingKey, UnboundKey, AES_256_GCM,
-        NONCE_LEN,
-    },
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
     digest,
-    error::Unspecified,
 };
+use tracing::info;
 
-use crate::Error;
+use crate::{Error, KeySource, SALT_LEN};
 
-#[must_use]
-struct CounterNonceSequence(u32);
-
-impl NonceSequence for CounterNonceSequence {
-    fn advance(&mut self) -> Result<Nonce, Unspecified> {
-        let mut nonce_bytes = vec![0; NONCE_LEN];
-
-        let bytes = self.0.to_be_bytes();
-        nonce_bytes[8..].copy_from_slice(&bytes);
-
-        self.0 += 1;
-        Nonce::try_assume_unique_for_key(&nonce_bytes)
-    }
-}
+/// Marks the versioned envelope: magic, version, salt, nonce, then the sealed data
+const ENVELOPE_MAGIC: &[u8] = b"NRS";
+const ENVELOPE_VERSION: u8 = 2;
+const HEADER_LEN: usize = ENVELOPE_MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
 
+/// Seal `data` with a random nonce and the per-install key of `app_name`
 pub(crate) fn aes_256_gcm_base64_encrypt<P, T, E>(
-    mut data: String,
+    data: String,
     file_path: P,
-    password: T,
+    app_name: T,
+    key_source: &KeySource,
     aad: E,
 ) -> Result<(), Error>
 where
@@ -42,46 +31,96 @@ where
     T: AsRef<str>,
     E: AsRef<str>,
 {
-    let key = digest::digest(&digest::SHA256, password.as_ref().as_bytes());
-    let unbound_key = UnboundKey::new(&AES_256_GCM, key.as_ref())
-        .map_err(|error| Error::Ring(error.to_string()))?;
-    let associated_data = Aad::from(aad.as_ref().as_bytes());
-
-    let mut sealing_key = SealingKey::new(unbound_key, CounterNonceSequence(1));
-
-    sealing_key
-        .seal_in_place_append_tag(associated_data, unsafe { data.as_mut_vec() })
+    let salt = crate::random_bytes::<SALT_LEN>()?;
+    let nonce = crate::random_bytes::<NONCE_LEN>()?;
+    let key = crate::file_key(app_name.as_ref(), key_source, &salt)?;
+
+    let mut envelope = Vec::with_capacity(HEADER_LEN + data.len() + AES_256_GCM.tag_len());
+    envelope.extend_from_slice(ENVELOPE_MAGIC);
+    envelope.push(ENVELOPE_VERSION);
+    envelope.extend_from_slice(&salt);
+    envelope.extend_from_slice(&nonce);
+
+    let mut data = data.into_bytes();
+    less_safe_key(key.as_ref())?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad.as_ref().as_bytes()),
+            &mut data,
+        )
         .map_err(|error| Error::Ring(error.to_string()))?;
+    envelope.extend_from_slice(&data);
 
-    fs::write(file_path, base64_simd::STANDARD.encode_to_string(data))?;
+    fs::write(file_path, base64_simd::STANDARD.encode_to_string(envelope))?;
 
     Ok(())
 }
 
-pub(crate) fn aes_256_gcm_base64_decrypt<P, T, E>(
+/// Open a file sealed by `aes_256_gcm_base64_encrypt`
+///
+/// Files written by older versions, sealed with a fixed nonce and the key derived from
+/// `legacy_password`, are opened too and rewritten in the current format
+pub(crate) fn aes_256_gcm_base64_decrypt<P, T, E, L>(
     file_path: P,
-    password: T,
+    app_name: T,
+    key_source: &KeySource,
     aad: E,
+    legacy_password: L,
 ) -> Result<String, Error>
 where
     P: AsRef<Path>,
     T: AsRef<str>,
     E: AsRef<str>,
+    L: AsRef<str>,
 {
-    let mut data = fs::read(file_path)?;
-    let data = base64_simd::STANDARD.decode_inplace(data.as_mut_slice())?;
+    let file_path = file_path.as_ref();
+    let data = base64_simd::STANDARD.decode_to_vec(fs::read(file_path)?)?;
+
+    if data.len() >= HEADER_LEN
+        && data.starts_with(ENVELOPE_MAGIC)
+        && data[ENVELOPE_MAGIC.len()] == ENVELOPE_VERSION
+    {
+        let (header, sealed) = data.split_at(HEADER_LEN);
+        let salt: &[u8; SALT_LEN] = header[ENVELOPE_MAGIC.len() + 1..][..SALT_LEN]
+            .try_into()
+            .unwrap();
+        let nonce = &header[HEADER_LEN - NONCE_LEN..];
+
+        let key = crate::file_key(app_name.as_ref(), key_source, salt)?;
+        if let Ok(decrypted) = open(key.as_ref(), nonce, aad.as_ref(), sealed.to_vec()) {
+            return Ok(decrypted);
+        }
+    }
 
-    let key = digest::digest(&digest::SHA256, password.as_ref().as_bytes());
-    let unbound_key = UnboundKey::new(&AES_256_GCM, key.as_ref())
-        .map_err(|error| Error::Ring(error.to_string()))?;
-    let associated_data = Aad::from(aad.as_ref().as_bytes());
+    let key = digest::digest(&digest::SHA256, legacy_password.as_ref().as_bytes());
+    let mut legacy_nonce = [0; NONCE_LEN];
+    legacy_nonce[NONCE_LEN - 1] = 1;
+    let decrypted = open(key.as_ref(), &legacy_nonce, aad.as_ref(), data)?;
+
+    info!(
+        "Migrate the file to the current encryption format: `{}`",
+        file_path.display()
+    );
+    aes_256_gcm_base64_encrypt(decrypted.clone(), file_path, app_name, key_source, aad)?;
+
+    Ok(decrypted)
+}
+
+fn less_safe_key(key: &[u8]) -> Result<LessSafeKey, Error> {
+    let unbound_key =
+        UnboundKey::new(&AES_256_GCM, key).map_err(|error| Error::Ring(error.to_string()))?;
+
+    Ok(LessSafeKey::new(unbound_key))
+}
 
-    let mut opening_key = OpeningKey::new(unbound_key, CounterNonceSequence(1));
-    let decrypted_data = opening_key
-        .open_in_place(associated_data, data)
+fn open(key: &[u8], nonce: &[u8], aad: &str, mut sealed: Vec<u8>) -> Result<String, Error> {
+    let nonce =
+        Nonce::try_assume_unique_for_key(nonce).map_err(|error| Error::Ring(error.to_string()))?;
+    let decrypted = less_safe_key(key)?
+        .open_in_place(nonce, Aad::from(aad.as_bytes()), &mut sealed)
         .map_err(|error| Error::Ring(error.to_string()))?;
 
-    Ok(simdutf8::basic::from_utf8(decrypted_data)?.to_string())
+    Ok(simdutf8::basic::from_utf8(decrypted)?.to_string())
 }
 
 pub(crate) fn aes_256_cbc_no_iv_base64_decrypt<T, E>(key: T, data: E) -> Result<Vec<u8>, Error>
@@ -104,16 +143,57 @@ mod tests {
 
     #[test]
     fn aes_256_gcm_base64() -> Result<(), Error> {
+        let key_source = KeySource::passphrase("passphrase");
+
         let dir = tempfile::tempdir()?;
         let file = dir.path().join("aes.txt");
 
         let data = String::from("Hello World");
-        aes_256_gcm_base64_encrypt(data, &file, "password", "aad")?;
+        aes_256_gcm_base64_encrypt(data.clone(), &file, "test-app", &key_source, "aad")?;
 
         assert!(file.is_file());
+        let first = fs::read(&file)?;
+
+        let decrypted =
+            aes_256_gcm_base64_decrypt(&file, "test-app", &key_source, "aad", "password")?;
+        assert_eq!(decrypted, "Hello World");
+
+        // Every write uses a new nonce
+        aes_256_gcm_base64_encrypt(data, &file, "test-app", &key_source, "aad")?;
+        assert_ne!(fs::read(&file)?, first);
+
+        Ok(())
+    }
+
+    #[test]
+    fn aes_256_gcm_base64_migrate() -> Result<(), Error> {
+        let key_source = KeySource::passphrase("passphrase");
+
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("aes.txt");
+
+        // The format written by older versions
+        let key = digest::digest(&digest::SHA256, b"password");
+        let mut nonce = [0; NONCE_LEN];
+        nonce[NONCE_LEN - 1] = 1;
+        let mut data = b"Hello World".to_vec();
+        less_safe_key(key.as_ref())?
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(b"aad"),
+                &mut data,
+            )
+            .unwrap();
+        fs::write(&file, base64_simd::STANDARD.encode_to_string(&data))?;
+
+        let decrypted =
+            aes_256_gcm_base64_decrypt(&file, "test-app", &key_source, "aad", "password")?;
+        assert_eq!(decrypted, "Hello World");
 
-        let decrypted = aes_256_gcm_base64_decrypt(file, "password", "aad")?;
+        let migrated = base64_simd::STANDARD.decode_to_vec(fs::read(&file)?)?;
+        assert!(migrated.starts_with(ENVELOPE_MAGIC));
 
+        let decrypted = aes_256_gcm_base64_decrypt(&file, "test-app", &key_source, "aad", "wrong")?;
         assert_eq!(decrypted, "Hello World");
 
         Ok(())
@@ -0,0 +1,277 @@
+use std::{marker::PhantomData, path::PathBuf, sync::Arc, time::Duration};
+
+use reqwest::{header::HeaderValue, Certificate};
+use tokio::fs;
+use url::Url;
+
+use crate::{
+    Cassette, Error, Freshness, KeySource, Profile, RateLimit, RetryPolicy, SpendingGuard,
+    SpendingPolicy, Storage,
+};
+
+/// Options shared by all clients, set by `ClientBuilder`
+#[derive(Clone)]
+pub(crate) struct ClientOptions {
+    pub(crate) proxy: Option<Url>,
+    pub(crate) no_proxy: bool,
+    pub(crate) cert_path: Option<PathBuf>,
+    pub(crate) host: Option<Url>,
+    pub(crate) cassette: Option<Arc<Cassette>>,
+    pub(crate) freshness: Freshness,
+    pub(crate) offline: bool,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) rate_limit: Option<RateLimit>,
+    pub(crate) image_rate_limit: Option<RateLimit>,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) user_agent: Option<String>,
+    pub(crate) spending: SpendingGuard,
+    pub(crate) profile: Profile,
+    pub(crate) storage: Storage,
+    pub(crate) key_source: KeySource,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        // Slow CI runners would hit the timeouts
+        let (connect_timeout, timeout) = if is_ci::cached() {
+            (None, None)
+        } else {
+            (Some(Duration::from_secs(10)), Some(Duration::from_secs(30)))
+        };
+
+        Self {
+            proxy: None,
+            no_proxy: false,
+            cert_path: None,
+            host: None,
+            cassette: None,
+            freshness: Freshness::default(),
+            offline: false,
+            retry_policy: RetryPolicy::default(),
+            rate_limit: None,
+            image_rate_limit: None,
+            connect_timeout,
+            timeout,
+            user_agent: None,
+            spending: SpendingGuard::default(),
+            profile: Profile::default(),
+            storage: Storage::default(),
+            key_source: KeySource::default(),
+        }
+    }
+}
+
+impl ClientOptions {
+    /// Check the options before the client is created
+    pub(crate) async fn validate(&self) -> Result<(), Error> {
+        if self.proxy.is_some() && self.no_proxy {
+            return Err(invalid("`proxy` and `no_proxy` can not be used together"));
+        }
+
+        if let Some(proxy) = &self.proxy {
+            if !["http", "https", "socks5", "socks5h"].contains(&proxy.scheme()) {
+                return Err(invalid(format!("Unsupported proxy scheme: `{proxy}`")));
+            }
+        }
+
+        if let Some(host) = &self.host {
+            if !["http", "https"].contains(&host.scheme()) || !host.has_host() {
+                return Err(invalid(format!("Invalid host: `{host}`")));
+            }
+        }
+
+        if let Some(cert_path) = &self.cert_path {
+            let pem = fs::read(cert_path).await.map_err(|err| {
+                invalid(format!(
+                    "Fail to read the certificate `{}`: {err}",
+                    cert_path.display()
+                ))
+            })?;
+            Certificate::from_pem(&pem).map_err(|err| {
+                invalid(format!(
+                    "Invalid certificate `{}`: {err}",
+                    cert_path.display()
+                ))
+            })?;
+        }
+
+        for (name, timeout) in [
+            ("connect_timeout", self.connect_timeout),
+            ("timeout", self.timeout),
+        ] {
+            if timeout.is_some_and(|timeout| timeout.is_zero()) {
+                return Err(invalid(format!("`{name}` must not be zero")));
+            }
+        }
+
+        if let Some(user_agent) = &self.user_agent {
+            HeaderValue::from_str(user_agent)
+                .map_err(|_| invalid(format!("Invalid user agent: `{user_agent}`")))?;
+        }
+
+        for rate_limit in [self.rate_limit, self.image_rate_limit].iter().flatten() {
+            if rate_limit.requests == 0 || rate_limit.burst == 0 || rate_limit.per.is_zero() {
+                return Err(invalid(format!("Invalid rate limit: `{rate_limit:?}`")));
+            }
+        }
+
+        if self.retry_policy.max_attempts == 0 {
+            return Err(invalid(
+                "`max_attempts` of the retry policy must not be zero",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn invalid<T>(msg: T) -> Error
+where
+    T: Into<String>,
+{
+    Error::InvalidOptions(msg.into())
+}
+
+/// Builder of a client, created by e.g. `SfacgClient::builder()`
+///
+/// The options are checked by `build`, a misconfigured client is never created
+#[must_use]
+pub struct ClientBuilder<C> {
+    pub(crate) options: ClientOptions,
+    marker: PhantomData<fn() -> C>,
+}
+
+impl<C> Default for ClientBuilder<C> {
+    fn default() -> Self {
+        Self {
+            options: ClientOptions::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<C> ClientBuilder<C> {
+    /// Use the options for a client of another type
+    pub(crate) fn cast<D>(self) -> ClientBuilder<D> {
+        ClientBuilder {
+            options: self.options,
+            marker: PhantomData,
+        }
+    }
+
+    /// Send all requests through the proxy
+    pub fn proxy(mut self, proxy: Url) -> Self {
+        self.options.proxy = Some(proxy);
+        self
+    }
+
+    /// Do not use proxy (environment variables used to set proxy are ignored)
+    pub fn no_proxy(mut self) -> Self {
+        self.options.no_proxy = true;
+        self
+    }
+
+    /// Trust the certificate, e.g. the one of a packet capture tool
+    pub fn cert<T>(mut self, cert_path: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        self.options.cert_path = Some(cert_path.into());
+        self
+    }
+
+    /// Set the base url of the Apis, e.g. a local mock server
+    pub fn host(mut self, host: Url) -> Self {
+        self.options.host = Some(host);
+        self
+    }
+
+    /// Record the HTTP traffic to the cassette, or replay it
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.options.cassette = Some(Arc::new(cassette));
+        self
+    }
+
+    /// Set how long the saved novel and volume information stays fresh
+    pub fn freshness(mut self, freshness: Freshness) -> Self {
+        self.options.freshness = freshness;
+        self
+    }
+
+    /// Only read the data saved in the database, never access the network
+    pub fn offline(mut self) -> Self {
+        self.options.offline = true;
+        self
+    }
+
+    /// Set when and how failed HTTP requests are retried
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.options.retry_policy = retry_policy;
+        self
+    }
+
+    /// Limit the rate of the requests sent to the Api host
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.options.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Limit the rate of the requests sent to the image host
+    pub fn image_rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.options.image_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Set the timeout of connecting to a host, 10s by default
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.options.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout of a whole request, 30s by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    /// Wait for the responses without a time limit
+    pub fn no_timeout(mut self) -> Self {
+        self.options.connect_timeout = None;
+        self.options.timeout = None;
+        self
+    }
+
+    /// Replace the user agent of all requests
+    pub fn user_agent<T>(mut self, user_agent: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.options.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Limit the currency spent by the client, or only report what would be bought
+    pub fn spending_policy(mut self, policy: SpendingPolicy) -> Self {
+        self.options.spending = SpendingGuard::new(policy);
+        self
+    }
+
+    /// Use the config file, the cookies and the cached user data of the profile
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.options.profile = profile;
+        self
+    }
+
+    /// Set where the config files, the cookies and the database are stored
+    pub fn storage(mut self, storage: Storage) -> Self {
+        self.options.storage = storage;
+        self
+    }
+
+    /// Set where the key that encrypts the config and cookie files comes from
+    pub fn key_source(mut self, key_source: KeySource) -> Self {
+        self.options.key_source = key_source;
+        self
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use bytes::Bytes;
+use http::{
+    header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, TRANSFER_ENCODING},
+    HeaderMap, HeaderName, HeaderValue, StatusCode,
+};
+use reqwest::{Client, Request, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{error, info};
+use url::form_urlencoded;
+
+use crate::Error;
+
+/// Whether a cassette records the real HTTP traffic or replays it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests over the network and save every request/response pair
+    Record,
+    /// Answer requests from the cassette file without touching the network
+    Replay,
+}
+
+/// Request/response pairs of a real session, used to replay it deterministically
+///
+/// Requests are matched by method, path and parameters, headers and the volatile
+/// signature parameters of each source are ignored
+#[must_use]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl Cassette {
+    /// Create a cassette that records into `path`, the file is written when it is dropped
+    pub fn record<T>(path: T) -> Self
+    where
+        T: AsRef<Path>,
+    {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            mode: CassetteMode::Record,
+            interactions: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Load a cassette from `path` to replay it
+    pub fn replay<T>(path: T) -> Result<Self, Error>
+    where
+        T: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        info!("Replay the cassette file: `{}`", path.display());
+
+        let interactions = serde_json::from_slice(&fs::read(&path)?)?;
+
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            interactions: Mutex::new(interactions),
+        })
+    }
+
+    /// Get the mode of the cassette
+    #[must_use]
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Write the recorded interactions to the cassette file
+    pub fn save(&self) -> Result<(), Error> {
+        if self.mode != CassetteMode::Record {
+            return Ok(());
+        }
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let json = serde_json::to_string_pretty(&*self.interactions.lock().unwrap())?;
+        fs::write(&self.path, json)?;
+
+        info!("Save the cassette file at: `{}`", self.path.display());
+
+        Ok(())
+    }
+
+    pub(crate) async fn send(
+        &self,
+        client: &Client,
+        rules: &CassetteRules,
+        request: RequestBuilder,
+    ) -> Result<Response, Error> {
+        let request = request.build()?;
+        let recorded_request = RecordedRequest::new(&request, rules)?;
+
+        match self.mode {
+            CassetteMode::Record => {
+                let response = client.execute(request).await?;
+
+                let status = response.status();
+                let mut headers = response.headers().clone();
+                for name in [CONTENT_ENCODING, CONTENT_LENGTH, TRANSFER_ENCODING] {
+                    headers.remove(name);
+                }
+                let body = response.bytes().await?;
+
+                self.interactions.lock().unwrap().push(Interaction {
+                    request: recorded_request,
+                    response: RecordedResponse::new(status, &headers, &body, rules),
+                });
+
+                Ok(build_response(status, headers, body))
+            }
+            CassetteMode::Replay => {
+                let interaction = {
+                    let mut interactions = self.interactions.lock().unwrap();
+
+                    let index = interactions
+                        .iter()
+                        .position(|interaction| interaction.request == recorded_request)
+                        .ok_or_else(|| {
+                            Error::Cassette(format!(
+                                "No matching interaction in the cassette: `{} {}`, params: `{:?}`",
+                                recorded_request.method,
+                                recorded_request.path,
+                                recorded_request.params
+                            ))
+                        })?;
+
+                    interactions.remove(index)
+                };
+
+                interaction.response.into_response()
+            }
+        }
+    }
+}
+
+impl Drop for Cassette {
+    fn drop(&mut self) {
+        if let Err(err) = self.save() {
+            error!("Fail to save cassette: {err}");
+        }
+    }
+}
+
+/// Source specific hooks that keep the cassette stable and readable
+#[derive(Clone, Copy)]
+pub(crate) struct CassetteRules {
+    /// Remove or rewrite the volatile request parameters
+    pub(crate) normalize: fn(&mut BTreeMap<String, String>) -> Result<(), Error>,
+    /// Decode the raw response body into JSON
+    pub(crate) decode: fn(&[u8]) -> Option<Value>,
+}
+
+impl Default for CassetteRules {
+    fn default() -> Self {
+        Self {
+            normalize: |_| Ok(()),
+            decode: |bytes| serde_json::from_slice(bytes).ok(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(PartialEq, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    path: String,
+    params: BTreeMap<String, String>,
+}
+
+impl RecordedRequest {
+    fn new(request: &Request, rules: &CassetteRules) -> Result<Self, Error> {
+        let mut params = BTreeMap::new();
+
+        for (key, value) in request.url().query_pairs() {
+            insert_param(&mut params, key.to_string(), value.to_string());
+        }
+
+        if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+            let content_type = request
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+
+            if content_type.starts_with("application/x-www-form-urlencoded") {
+                for (key, value) in form_urlencoded::parse(body) {
+                    insert_param(&mut params, key.to_string(), value.to_string());
+                }
+            } else if let Ok(Value::Object(object)) = serde_json::from_slice(body) {
+                for (key, value) in object {
+                    let value = match value {
+                        Value::String(value) => value,
+                        value => value.to_string(),
+                    };
+                    insert_param(&mut params, key, value);
+                }
+            } else {
+                insert_param(
+                    &mut params,
+                    String::from("body"),
+                    base64_simd::STANDARD.encode_to_string(body),
+                );
+            }
+        }
+
+        (rules.normalize)(&mut params)?;
+
+        Ok(Self {
+            method: request.method().to_string(),
+            path: request.url().path().to_string(),
+            params,
+        })
+    }
+}
+
+fn insert_param(params: &mut BTreeMap<String, String>, key: String, value: String) {
+    params
+        .entry(key)
+        .and_modify(|old| {
+            old.push(',');
+            old.push_str(&value);
+        })
+        .or_insert(value);
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    json: Option<Value>,
+    body: String,
+}
+
+impl RecordedResponse {
+    fn new(status: StatusCode, headers: &HeaderMap, body: &[u8], rules: &CassetteRules) -> Self {
+        Self {
+            status: status.as_u16(),
+            headers: headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    value
+                        .to_str()
+                        .ok()
+                        .map(|value| (name.to_string(), value.to_string()))
+                })
+                .collect(),
+            json: (rules.decode)(body),
+            body: base64_simd::STANDARD.encode_to_string(body),
+        }
+    }
+
+    fn into_response(self) -> Result<Response, Error> {
+        let status = StatusCode::from_u16(self.status)?;
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in self.headers {
+            headers.append(
+                HeaderName::try_from(name).map_err(|err| Error::Cassette(err.to_string()))?,
+                HeaderValue::try_from(value)?,
+            );
+        }
+
+        let body = base64_simd::STANDARD.decode_to_vec(self.body)?;
+
+        Ok(build_response(status, headers, Bytes::from(body)))
+    }
+}
+
+fn build_response(status: StatusCode, headers: HeaderMap, body: Bytes) -> Response {
+    let mut response = http::Response::new(body);
+    *response.status_mut() = status;
+    *response.headers_mut() = headers;
+
+    Response::from(response)
+}
@@ -1,18 +1,20 @@
 use std::{
+    collections::HashMap,
     fmt::{self, Display},
     ops::{Range, RangeFrom, RangeTo},
-    path::PathBuf,
+    time::Duration,
 };
 
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use image::DynamicImage;
+use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::Error;
 
 /// Logged-in user information
 #[must_use]
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UserInfo {
     /// User's nickname
     pub nickname: String,
@@ -20,6 +22,18 @@ pub struct UserInfo {
     pub avatar: Option<Url>,
 }
 
+/// Bookshelf of the logged-in user
+#[must_use]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookshelf {
+    /// Bookshelf id
+    pub id: u32,
+    /// Bookshelf name
+    pub name: String,
+    /// Ids of the novels on the bookshelf
+    pub novel_ids: Vec<u32>,
+}
+
 /// Novel information
 #[must_use]
 #[derive(Debug, Default)]
@@ -28,6 +42,8 @@ pub struct NovelInfo {
     pub id: u32,
     /// Novel name
     pub name: String,
+    /// Author id
+    pub author_id: Option<u32>,
     /// Author name
     pub author_name: String,
     /// Url of the novel cover
@@ -56,9 +72,36 @@ impl PartialEq for NovelInfo {
     }
 }
 
+/// The information of a novel found by a search, without the introduction and timestamps
+/// that are only returned by `novel_info`
+#[must_use]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NovelSummary {
+    /// Novel id
+    pub id: u32,
+    /// Novel name
+    pub name: String,
+    /// Author name
+    pub author_name: Option<String>,
+    /// Url of the novel cover
+    pub cover_url: Option<Url>,
+    /// Novel word count
+    pub word_count: Option<u32>,
+    /// Is the novel a VIP
+    pub is_vip: Option<bool>,
+    /// Is the novel finished
+    pub is_finished: Option<bool>,
+    /// Novel last update time
+    pub update_time: Option<NaiveDateTime>,
+    /// Novel category
+    pub category: Option<Category>,
+    /// Novel tags
+    pub tags: Option<Vec<Tag>>,
+}
+
 /// Novel category
 #[must_use]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Category {
     /// Category id
     pub id: Option<u16>,
@@ -76,7 +119,7 @@ impl Display for Category {
 
 /// Novel tag
 #[must_use]
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Tag {
     /// Tag id
     pub id: Option<u16>,
@@ -95,7 +138,7 @@ pub type VolumeInfos = Vec<VolumeInfo>;
 
 /// Volume information
 #[must_use]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VolumeInfo {
     /// Volume title
     pub title: String,
@@ -105,7 +148,7 @@ pub struct VolumeInfo {
 
 /// Chapter information
 #[must_use]
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct ChapterInfo {
     /// Novel id
     pub novel_id: Option<u32>,
@@ -146,6 +189,46 @@ impl ChapterInfo {
     }
 }
 
+/// Result of a chapter bought by `buy_chapters`
+#[must_use]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChapterPurchase {
+    /// Chapter id
+    pub chapter_id: u32,
+    /// Chapter price
+    pub price: Option<u16>,
+    /// Purchase status
+    pub status: PurchaseStatus,
+}
+
+/// Purchase status of a chapter
+#[must_use]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PurchaseStatus {
+    /// The chapter has been bought
+    Bought,
+    /// The chapter would be bought, but nothing is spent in the dry-run mode
+    DryRun,
+    /// The chapter is free, already purchased or invalid
+    Skipped,
+    /// The order containing the chapter failed
+    Failed(String),
+}
+
+/// Purchase saved in the audit log
+#[must_use]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PurchaseRecord {
+    /// Novel id
+    pub novel_id: Option<u32>,
+    /// Chapter id
+    pub chapter_id: u32,
+    /// Price paid for the chapter
+    pub price: Option<u16>,
+    /// Purchase time
+    pub purchased_at: NaiveDateTime,
+}
+
 /// Content information
 pub type ContentInfos = Vec<ContentInfo>;
 
@@ -159,6 +242,74 @@ pub enum ContentInfo {
     Image(Url),
 }
 
+/// Comment on a chapter or a paragraph, or reply to a review
+#[must_use]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    /// Comment id
+    pub id: u32,
+    /// Name of the commenter
+    pub author_name: String,
+    /// Avatar of the commenter
+    pub author_avatar: Option<Url>,
+    /// Comment content
+    pub content: String,
+    /// Comment creation time
+    pub create_time: Option<NaiveDateTime>,
+    /// Number of likes
+    pub like_count: Option<u32>,
+    /// Number of replies
+    pub reply_count: Option<u32>,
+}
+
+/// Review or discussion thread of a novel
+#[must_use]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Review {
+    /// Review id
+    pub id: u32,
+    /// Review title
+    pub title: Option<String>,
+    /// Review content
+    pub content: String,
+    /// Name of the reviewer
+    pub author_name: String,
+    /// Avatar of the reviewer
+    pub author_avatar: Option<Url>,
+    /// Review creation time
+    pub create_time: Option<NaiveDateTime>,
+    /// Number of likes
+    pub like_count: Option<u32>,
+    /// Number of replies
+    pub reply_count: Option<u32>,
+}
+
+/// Author information
+#[must_use]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Author {
+    /// Author id
+    pub id: u32,
+    /// Author name
+    pub name: String,
+    /// Avatar of the author
+    pub avatar: Option<Url>,
+    /// Author introduction
+    pub introduction: Option<Vec<String>>,
+    /// Number of followers
+    pub follower_count: Option<u32>,
+}
+
+/// Reading progress of a novel saved on the server
+#[must_use]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadingProgress {
+    /// Id of the last read chapter
+    pub chapter_id: u32,
+    /// Time when the chapter was read
+    pub read_time: Option<NaiveDateTime>,
+}
+
 /// Options used by the search
 #[derive(Debug, Default)]
 pub struct Options {
@@ -178,6 +329,27 @@ pub struct Options {
     pub update_days: Option<u8>,
     /// Word count
     pub word_count: Option<WordCountRange>,
+    /// Author name, only supported by sources that filter by author on the server
+    pub author: Option<String>,
+    /// Order of the results, the default order of the source is used if not set
+    pub sort: Option<SortOrder>,
+}
+
+/// Order of the search results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Most popular first
+    Popular,
+    /// Most recently updated first
+    Latest,
+    /// Most bookmarked first
+    Bookmarks,
+    /// Most monthly tickets first
+    Tickets,
+    /// Most subscribed first
+    Sales,
+    /// Most words first
+    WordCount,
 }
 
 /// Word count range
@@ -191,18 +363,59 @@ pub enum WordCountRange {
     RangeTo(RangeTo<u32>),
 }
 
-/// Traits that abstract client behavior
-#[trait_variant::make(Send)]
-pub trait Client {
-    /// set proxy
-    fn proxy(&mut self, proxy: Url);
+/// Kind of a ranking list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankingKind {
+    /// Most read
+    Popular,
+    /// Most monthly tickets
+    Tickets,
+    /// Most popular new novels
+    NewBooks,
+    /// Most subscribed
+    Bestsellers,
+}
 
-    /// Do not use proxy (environment variables used to set proxy are ignored)
-    fn no_proxy(&mut self);
+/// Period counted by a ranking list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankingPeriod {
+    /// This week
+    Week,
+    /// This month
+    Month,
+    /// All time
+    Total,
+}
+
+/// How long the novel and volume information saved in the database stays fresh
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Freshness {
+    /// Always fetch from the network, the result is still saved
+    #[default]
+    NoCache,
+    /// Use the saved information if it was fetched within the duration
+    MaxAge(Duration),
+    /// Use the saved information whenever it exists
+    Forever,
+}
 
-    /// Set the certificate path for use with packet capture tools
-    fn cert(&mut self, cert_path: PathBuf);
+impl Freshness {
+    pub(crate) fn is_fresh(&self, fetched_at: NaiveDateTime) -> bool {
+        match self {
+            Freshness::NoCache => false,
+            Freshness::MaxAge(max_age) => match (Utc::now().naive_utc() - fetched_at).to_std() {
+                Ok(age) => age < *max_age,
+                // Fetched in the future, the clock is wrong and the age is unknown
+                Err(_) => false,
+            },
+            Freshness::Forever => true,
+        }
+    }
+}
 
+/// Traits that abstract client behavior
+#[trait_variant::make(Send)]
+pub trait Client {
     /// Stop the client, save the data
     async fn shutdown(&self) -> Result<(), Error>;
 
@@ -227,18 +440,101 @@ pub trait Client {
     /// Get the favorite novel of the logged-in user and return the novel id
     async fn bookshelf_infos(&self) -> Result<Vec<u32>, Error>;
 
+    /// Get the bookshelves of the logged-in user and the novels on each of them
+    async fn bookshelves(&self) -> Result<Vec<Bookshelf>, Error>;
+
+    /// Add the novel to the bookshelf
+    async fn add_to_bookshelf(&self, novel_id: u32, shelf_id: u32) -> Result<(), Error>;
+
+    /// Remove the novel from the bookshelf
+    async fn remove_from_bookshelf(&self, novel_id: u32, shelf_id: u32) -> Result<(), Error>;
+
+    /// Move the novel from one bookshelf to another
+    async fn move_between_shelves(
+        &self,
+        novel_id: u32,
+        from_shelf_id: u32,
+        to_shelf_id: u32,
+    ) -> Result<(), Error>;
+
     /// Get Novel Information
     async fn novel_info(&self, id: u32) -> Result<Option<NovelInfo>, Error>;
 
+    /// Get author information, return `None` if the author does not exist
+    async fn author_info(&self, id: u32) -> Result<Option<Author>, Error>;
+
+    /// Get all novels published by the author
+    async fn author_novels(&self, id: u32) -> Result<Vec<NovelSummary>, Error>;
+
     /// Get volume Information
     async fn volume_infos(&self, id: u32) -> Result<Option<VolumeInfos>, Error>;
 
+    /// Get the novel information saved in the database, without network access
+    async fn cached_novel_info(&self, id: u32) -> Result<Option<NovelInfo>, Error>;
+
+    /// Get the volume information saved in the database, without network access
+    async fn cached_volume_infos(&self, id: u32) -> Result<Option<VolumeInfos>, Error>;
+
     /// Get content Information
     async fn content_infos(&self, info: &ChapterInfo) -> Result<ContentInfos, Error>;
 
-    /// Buy chapter
+    /// Get a page of the comments on the chapter, return `None` after the last page
+    async fn chapter_comments(
+        &self,
+        info: &ChapterInfo,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error>;
+
+    /// Get the number of comments on each paragraph of the chapter, keyed by the index of the
+    /// paragraph in the `ContentInfos` returned by `content_infos`
+    async fn paragraph_comment_counts(
+        &self,
+        info: &ChapterInfo,
+    ) -> Result<HashMap<usize, u32>, Error>;
+
+    /// Get a page of the comments on the paragraph at `index` in the `ContentInfos` returned by
+    /// `content_infos`, return `None` after the last page
+    async fn paragraph_comments(
+        &self,
+        info: &ChapterInfo,
+        index: usize,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error>;
+
+    /// Get a page of the reviews of the novel, return `None` after the last page
+    async fn novel_reviews(&self, novel_id: u32, page: u16) -> Result<Option<Vec<Review>>, Error>;
+
+    /// Get a page of the replies to the review, return `None` after the last page
+    async fn review_replies(
+        &self,
+        review_id: u32,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error>;
+
+    /// Get the reading progress of the novel, return `None` if it has never been read
+    async fn reading_progress(&self, novel_id: u32) -> Result<Option<ReadingProgress>, Error>;
+
+    /// Save the last read chapter of the novel
+    async fn set_reading_progress(&self, novel_id: u32, chapter_id: u32) -> Result<(), Error>;
+
+    /// Buy chapter, nothing is bought in the dry-run mode of the spending policy
     async fn buy_chapter(&self, info: &ChapterInfo) -> Result<(), Error>;
 
+    /// Buy the chapters with as few orders as the source allows, return the result of every
+    /// chapter in the same order
+    ///
+    /// The total price is checked against the balance before anything is bought
+    async fn buy_chapters(&self, infos: &[ChapterInfo]) -> Result<Vec<ChapterPurchase>, Error>;
+
+    /// Buy all chapters of the volume that have not been purchased
+    async fn buy_volume(&self, info: &VolumeInfo) -> Result<Vec<ChapterPurchase>, Error>;
+
+    /// Buy all chapters of the novel that have not been purchased
+    async fn buy_all_remaining(&self, novel_id: u32) -> Result<Vec<ChapterPurchase>, Error>;
+
+    /// Get the purchases saved in the audit log, oldest first
+    async fn purchase_records(&self) -> Result<Vec<PurchaseRecord>, Error>;
+
     /// Download image
     async fn image(&self, url: &Url) -> Result<DynamicImage, Error>;
 
@@ -255,4 +551,37 @@ pub trait Client {
         page: u16,
         size: u16,
     ) -> Result<Option<Vec<u32>>, Error>;
+
+    /// Search a page of matching novels with the page size that suits the source,
+    /// return `None` after the last page
+    async fn search_summaries(
+        &self,
+        option: &Options,
+        page: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error>;
+
+    /// Get a page of the ranking list, return `None` after the last page
+    async fn rankings(
+        &self,
+        kind: RankingKind,
+        period: RankingPeriod,
+        page: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error>;
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    #[test]
+    fn freshness() {
+        let max_age = Freshness::MaxAge(Duration::from_secs(60));
+        let now = Utc::now().naive_utc();
+
+        assert!(max_age.is_fresh(now - TimeDelta::seconds(30)));
+        assert!(!max_age.is_fresh(now - TimeDelta::seconds(90)));
+        assert!(!max_age.is_fresh(now + TimeDelta::seconds(30)));
+    }
 }
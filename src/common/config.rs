@@ -3,19 +3,25 @@ use std::{fs, path::PathBuf};
 use serde::{de::DeserializeOwned, Serialize};
 use tracing::{error, info};
 
-use crate::Error;
+use crate::{Error, KeySource, Profile, Storage};
 
-const CONFIG_FILE_NAME: &str = "config.toml";
+pub(crate) const CONFIG_FILE_NAME: &str = "config.toml";
 
-const CONFIG_FILE_PASSWORD: &str = "nupwuz-toxvif-0timNo";
+/// Only used to open the config files written by older versions
+const LEGACY_CONFIG_FILE_PASSWORD: &str = "nupwuz-toxvif-0timNo";
 const CONFIG_FILE_AAD: &str = "novel-rs-config";
 
-pub(crate) fn load_config_file<T, R>(app_name: T) -> Result<Option<R>, Error>
+pub(crate) fn load_config_file<T, R>(
+    app_name: T,
+    profile: &Profile,
+    storage: &Storage,
+    key_source: &KeySource,
+) -> Result<Option<R>, Error>
 where
     T: AsRef<str>,
     R: DeserializeOwned,
 {
-    let config_file_path = config_file_path(app_name)?;
+    let config_file_path = config_file_path(&app_name, profile, storage)?;
 
     if config_file_path.try_exists()? {
         info!(
@@ -25,8 +31,10 @@ where
 
         if let Ok(config) = crate::aes_256_gcm_base64_decrypt(
             config_file_path,
-            CONFIG_FILE_PASSWORD,
+            app_name,
+            key_source,
             CONFIG_FILE_AAD,
+            LEGACY_CONFIG_FILE_PASSWORD,
         ) {
             let config: R = toml::from_str(&config)?;
             Ok(Some(config))
@@ -46,30 +54,37 @@ where
     }
 }
 
-pub(crate) fn save_config_file<T, E>(app_name: T, config: E) -> Result<(), Error>
+pub(crate) fn save_config_file<T, E>(
+    app_name: T,
+    profile: &Profile,
+    storage: &Storage,
+    key_source: &KeySource,
+    config: E,
+) -> Result<(), Error>
 where
     T: AsRef<str>,
     E: Serialize,
 {
-    let config_file_path = config_file_path(app_name)?;
+    let config_file_path = config_file_path(&app_name, profile, storage)?;
 
     info!("Save the config file at: `{}`", config_file_path.display());
 
     crate::aes_256_gcm_base64_encrypt(
         toml::to_string(&config)?,
         config_file_path,
-        CONFIG_FILE_PASSWORD,
+        app_name,
+        key_source,
         CONFIG_FILE_AAD,
     )?;
 
     Ok(())
 }
 
-fn config_file_path<T>(app_name: T) -> Result<PathBuf, Error>
+fn config_file_path<T>(app_name: &T, profile: &Profile, storage: &Storage) -> Result<PathBuf, Error>
 where
     T: AsRef<str>,
 {
-    let mut config_file_path = crate::config_dir_path(app_name.as_ref())?;
+    let mut config_file_path = profile.config_dir(app_name.as_ref(), storage)?;
     config_file_path.push(CONFIG_FILE_NAME);
 
     Ok(config_file_path)
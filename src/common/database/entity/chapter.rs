@@ -0,0 +1,26 @@
+use chrono::NaiveDateTime;
+use sea_orm::entity::prelude::*;
+
+#[derive(Debug, PartialEq, Eq, Clone, DeriveEntityModel)]
+#[sea_orm(table_name = "chapter")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub novel_id: u32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub volume_position: u32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub position: u32,
+    pub id: u32,
+    pub title: String,
+    pub is_vip: Option<bool>,
+    pub price: Option<u16>,
+    pub is_valid: Option<bool>,
+    pub word_count: Option<u32>,
+    pub create_time: Option<NaiveDateTime>,
+    pub update_time: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
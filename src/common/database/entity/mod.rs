@@ -1,4 +1,14 @@
+pub mod cache;
+pub mod chapter;
+pub mod chapter_payment;
 pub mod image;
+pub mod novel;
+pub mod purchase;
 pub mod text;
+pub mod volume;
 
-pub use self::{image::Entity as Image, text::Entity as Text};
+pub use self::{
+    cache::Entity as Cache, chapter::Entity as Chapter, chapter_payment::Entity as ChapterPayment,
+    image::Entity as Image, novel::Entity as Novel, purchase::Entity as Purchase,
+    text::Entity as Text, volume::Entity as Volume,
+};
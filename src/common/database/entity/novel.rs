@@ -0,0 +1,27 @@
+use chrono::NaiveDateTime;
+use sea_orm::entity::prelude::*;
+
+#[derive(Debug, PartialEq, Eq, Clone, DeriveEntityModel)]
+#[sea_orm(table_name = "novel")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: u32,
+    pub name: String,
+    pub author_name: String,
+    pub author_id: Option<u32>,
+    pub cover_url: Option<String>,
+    pub introduction: Option<String>,
+    pub word_count: Option<u32>,
+    pub is_vip: Option<bool>,
+    pub is_finished: Option<bool>,
+    pub create_time: Option<NaiveDateTime>,
+    pub update_time: Option<NaiveDateTime>,
+    pub category: Option<String>,
+    pub tags: Option<String>,
+    pub fetched_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
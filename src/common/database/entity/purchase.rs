@@ -0,0 +1,19 @@
+use chrono::NaiveDateTime;
+use sea_orm::entity::prelude::*;
+
+#[derive(Debug, PartialEq, Eq, Clone, DeriveEntityModel)]
+#[sea_orm(table_name = "purchase")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub account: Option<String>,
+    pub novel_id: Option<u32>,
+    pub chapter_id: u32,
+    pub price: Option<u16>,
+    pub purchased_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
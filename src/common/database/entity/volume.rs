@@ -0,0 +1,18 @@
+use chrono::NaiveDateTime;
+use sea_orm::entity::prelude::*;
+
+#[derive(Debug, PartialEq, Eq, Clone, DeriveEntityModel)]
+#[sea_orm(table_name = "volume")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub novel_id: u32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub position: u32,
+    pub title: String,
+    pub fetched_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Copy, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
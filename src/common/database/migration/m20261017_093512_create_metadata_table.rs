@@ -0,0 +1,150 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[must_use]
+#[derive(Iden)]
+enum Novel {
+    Table,
+    Id,
+    Name,
+    AuthorName,
+    CoverUrl,
+    Introduction,
+    WordCount,
+    IsVip,
+    IsFinished,
+    CreateTime,
+    UpdateTime,
+    Category,
+    Tags,
+    FetchedAt,
+}
+
+#[must_use]
+#[derive(Iden)]
+enum Volume {
+    Table,
+    NovelId,
+    Position,
+    Title,
+    FetchedAt,
+}
+
+#[must_use]
+#[derive(Iden)]
+enum Chapter {
+    Table,
+    Id,
+    NovelId,
+    VolumePosition,
+    Position,
+    Title,
+    IsVip,
+    Price,
+    PaymentRequired,
+    IsValid,
+    WordCount,
+    CreateTime,
+    UpdateTime,
+}
+
+#[must_use]
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Novel::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Novel::Id).integer().not_null().primary_key())
+                    .col(ColumnDef::new(Novel::Name).string().not_null())
+                    .col(ColumnDef::new(Novel::AuthorName).string().not_null())
+                    .col(ColumnDef::new(Novel::CoverUrl).string())
+                    .col(ColumnDef::new(Novel::Introduction).text())
+                    .col(ColumnDef::new(Novel::WordCount).integer())
+                    .col(ColumnDef::new(Novel::IsVip).boolean())
+                    .col(ColumnDef::new(Novel::IsFinished).boolean())
+                    .col(ColumnDef::new(Novel::CreateTime).date_time())
+                    .col(ColumnDef::new(Novel::UpdateTime).date_time())
+                    .col(ColumnDef::new(Novel::Category).text())
+                    .col(ColumnDef::new(Novel::Tags).text())
+                    .col(ColumnDef::new(Novel::FetchedAt).date_time().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Volume::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Volume::NovelId).integer().not_null())
+                    .col(ColumnDef::new(Volume::Position).integer().not_null())
+                    .col(ColumnDef::new(Volume::Title).string().not_null())
+                    .col(ColumnDef::new(Volume::FetchedAt).date_time().not_null())
+                    .primary_key(Index::create().col(Volume::NovelId).col(Volume::Position))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Chapter::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Chapter::NovelId).integer().not_null())
+                    .col(ColumnDef::new(Chapter::VolumePosition).integer().not_null())
+                    .col(ColumnDef::new(Chapter::Position).integer().not_null())
+                    .col(ColumnDef::new(Chapter::Id).integer().not_null())
+                    .col(ColumnDef::new(Chapter::Title).string().not_null())
+                    .col(ColumnDef::new(Chapter::IsVip).boolean())
+                    .col(ColumnDef::new(Chapter::Price).small_integer())
+                    .col(ColumnDef::new(Chapter::PaymentRequired).boolean())
+                    .col(ColumnDef::new(Chapter::IsValid).boolean())
+                    .col(ColumnDef::new(Chapter::WordCount).integer())
+                    .col(ColumnDef::new(Chapter::CreateTime).date_time())
+                    .col(ColumnDef::new(Chapter::UpdateTime).date_time())
+                    .primary_key(
+                        Index::create()
+                            .col(Chapter::NovelId)
+                            .col(Chapter::VolumePosition)
+                            .col(Chapter::Position),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-chapter-id")
+                    .table(Chapter::Table)
+                    .col(Chapter::Id)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Novel::Table).if_exists().to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Volume::Table).if_exists().to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Chapter::Table).if_exists().to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[must_use]
+#[derive(Iden)]
+enum Cache {
+    Table,
+    Key,
+    Value,
+    FetchedAt,
+}
+
+#[must_use]
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Cache::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(Cache::Key).string().not_null().primary_key())
+                    .col(ColumnDef::new(Cache::Value).text().not_null())
+                    .col(ColumnDef::new(Cache::FetchedAt).date_time().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Cache::Table).if_exists().to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[must_use]
+#[derive(Iden)]
+enum Novel {
+    Table,
+    AuthorId,
+}
+
+#[must_use]
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Novel::Table)
+                    .add_column(ColumnDef::new(Novel::AuthorId).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Novel::Table)
+                    .drop_column(Novel::AuthorId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[must_use]
+#[derive(Iden)]
+enum Purchase {
+    Table,
+    Id,
+    Account,
+    NovelId,
+    ChapterId,
+    Price,
+    PurchasedAt,
+}
+
+#[must_use]
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Purchase::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Purchase::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Purchase::Account).string())
+                    .col(ColumnDef::new(Purchase::NovelId).integer())
+                    .col(ColumnDef::new(Purchase::ChapterId).integer().not_null())
+                    .col(ColumnDef::new(Purchase::Price).small_integer())
+                    .col(ColumnDef::new(Purchase::PurchasedAt).date_time().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-purchase-novel-id")
+                    .table(Purchase::Table)
+                    .col(Purchase::NovelId)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Purchase::Table).if_exists().to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
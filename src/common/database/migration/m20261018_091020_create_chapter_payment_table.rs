@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+#[must_use]
+#[derive(Iden)]
+enum ChapterPayment {
+    Table,
+    Account,
+    ChapterId,
+    NovelId,
+    PaymentRequired,
+}
+
+#[must_use]
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChapterPayment::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ChapterPayment::Account).string().not_null())
+                    .col(
+                        ColumnDef::new(ChapterPayment::ChapterId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ChapterPayment::NovelId).integer().not_null())
+                    .col(ColumnDef::new(ChapterPayment::PaymentRequired).boolean())
+                    .primary_key(
+                        Index::create()
+                            .col(ChapterPayment::Account)
+                            .col(ChapterPayment::ChapterId),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-chapter-payment-novel-id")
+                    .table(ChapterPayment::Table)
+                    .col(ChapterPayment::NovelId)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(ChapterPayment::Table)
+                    .if_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,9 @@
 mod m20221215_070928_create_table;
+mod m20261017_093512_create_metadata_table;
+mod m20261017_141207_create_cache_table;
+mod m20261017_201530_add_novel_author_id;
+mod m20261017_223045_create_purchase_table;
+mod m20261018_091020_create_chapter_payment_table;
 
 pub use sea_orm_migration::prelude::*;
 
@@ -7,6 +12,13 @@ pub struct Migrator;
 
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20221215_070928_create_table::Migration)]
+        vec![
+            Box::new(m20221215_070928_create_table::Migration),
+            Box::new(m20261017_093512_create_metadata_table::Migration),
+            Box::new(m20261017_141207_create_cache_table::Migration),
+            Box::new(m20261017_201530_add_novel_author_id::Migration),
+            Box::new(m20261017_223045_create_purchase_table::Migration),
+            Box::new(m20261018_091020_create_chapter_payment_table::Migration),
+        ]
     }
 }
@@ -1,12 +1,16 @@
 mod entity;
 mod migration;
 
-use std::{io::Cursor, path::PathBuf, time::Duration};
+use std::{collections::HashMap, io::Cursor, path::PathBuf, time::Duration};
 
 use async_compression::tokio::{bufread::ZstdDecoder, write::ZstdEncoder};
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use image::{io::Reader, DynamicImage};
-use sea_orm::{ActiveModelTrait, ConnectOptions, Database, DatabaseConnection, EntityTrait};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, ConnectOptions, Database, DatabaseConnection,
+    EntityTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
+use serde::{de::DeserializeOwned, Serialize};
 use tokio::{
     fs,
     io::{AsyncReadExt, AsyncWriteExt, BufReader},
@@ -15,14 +19,18 @@ use tracing::{error, info};
 use url::Url;
 
 use self::{
-    entity::{Image, Text},
+    entity::{Cache, Chapter, ChapterPayment, Image, Novel, Purchase, Text, Volume},
     migration::{Migrator, MigratorTrait},
 };
-use crate::{ChapterInfo, Error};
+use crate::{
+    ChapterInfo, Error, Freshness, NovelInfo, Profile, PurchaseRecord, Storage, VolumeInfo,
+    VolumeInfos,
+};
 
 #[must_use]
 pub(crate) struct NovelDB {
     db: DatabaseConnection,
+    cache_namespace: Option<String>,
 }
 
 #[must_use]
@@ -40,11 +48,43 @@ pub(crate) enum FindImageResult {
     None,
 }
 
+/// Data saved as JSON, so that it can be read in offline mode
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum CacheKey {
+    UserInfo,
+    Bookshelves,
+    Categories,
+    Tags,
+}
+
+impl CacheKey {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CacheKey::UserInfo => "user_info",
+            CacheKey::Bookshelves => "bookshelves",
+            CacheKey::Categories => "categories",
+            CacheKey::Tags => "tags",
+        }
+    }
+
+    /// Whether the data belongs to the logged-in account rather than the source
+    fn per_account(&self) -> bool {
+        matches!(self, CacheKey::UserInfo | CacheKey::Bookshelves)
+    }
+}
+
 impl NovelDB {
     const DB_NAME: &'static str = "novel.db";
 
-    pub(crate) async fn new(app_name: &str) -> Result<Self, Error> {
-        let db_path = NovelDB::db_path(app_name)?;
+    // Stay below the SQLite limit on the number of bound variables
+    const INSERT_CHUNK_SIZE: usize = 512;
+
+    pub(crate) async fn new(
+        app_name: &str,
+        profile: &Profile,
+        storage: &Storage,
+    ) -> Result<Self, Error> {
+        let db_path = NovelDB::db_path(app_name, profile, storage)?;
 
         if fs::try_exists(&db_path).await? {
             info!("The database file is located at `{}`", db_path.display());
@@ -76,7 +116,10 @@ impl NovelDB {
             Migrator::up(&db, None).await?;
         }
 
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            cache_namespace: profile.cache_namespace().map(ToString::to_string),
+        })
     }
 
     #[cfg(test)]
@@ -106,6 +149,19 @@ impl NovelDB {
         }
     }
 
+    /// Get the saved text even if it is outdated
+    pub(crate) async fn find_saved_text(&self, info: &ChapterInfo) -> Result<String, Error> {
+        match Text::find_by_id(info.id).one(&self.db).await? {
+            Some(model) => {
+                Ok(unsafe { String::from_utf8_unchecked(zstd_decompress(&model.content).await?) })
+            }
+            None => Err(Error::NotCached(format!(
+                "the text of chapter `{}`",
+                info.id
+            ))),
+        }
+    }
+
     pub(crate) async fn insert_text<T>(&self, info: &ChapterInfo, text: T) -> Result<(), Error>
     where
         T: AsRef<str>,
@@ -163,8 +219,324 @@ impl NovelDB {
         Ok(())
     }
 
-    fn db_path(app_name: &str) -> Result<PathBuf, Error> {
-        let mut db_path = crate::data_dir_path(app_name)?;
+    pub(crate) async fn find_novel_info(
+        &self,
+        id: u32,
+        freshness: Freshness,
+    ) -> Result<Option<NovelInfo>, Error> {
+        match Novel::find_by_id(id).one(&self.db).await? {
+            Some(model) if freshness.is_fresh(model.fetched_at) => Ok(Some(NovelInfo {
+                id: model.id,
+                name: model.name,
+                author_id: model.author_id,
+                author_name: model.author_name,
+                cover_url: model.cover_url.map(|url| Url::parse(&url)).transpose()?,
+                introduction: from_json(model.introduction)?,
+                word_count: model.word_count,
+                is_vip: model.is_vip,
+                is_finished: model.is_finished,
+                create_time: model.create_time,
+                update_time: model.update_time,
+                category: from_json(model.category)?,
+                tags: from_json(model.tags)?,
+            })),
+            _ => Ok(None),
+        }
+    }
+
+    pub(crate) async fn save_novel_info(&self, info: &NovelInfo) -> Result<(), Error> {
+        let model = entity::novel::ActiveModel {
+            id: sea_orm::Set(info.id),
+            name: sea_orm::Set(info.name.clone()),
+            author_name: sea_orm::Set(info.author_name.clone()),
+            author_id: sea_orm::Set(info.author_id),
+            cover_url: sea_orm::Set(info.cover_url.as_ref().map(Url::to_string)),
+            introduction: sea_orm::Set(to_json(&info.introduction)?),
+            word_count: sea_orm::Set(info.word_count),
+            is_vip: sea_orm::Set(info.is_vip),
+            is_finished: sea_orm::Set(info.is_finished),
+            create_time: sea_orm::Set(info.create_time),
+            update_time: sea_orm::Set(info.update_time),
+            category: sea_orm::Set(to_json(&info.category)?),
+            tags: sea_orm::Set(to_json(&info.tags)?),
+            fetched_at: sea_orm::Set(Utc::now().naive_utc()),
+        };
+
+        let txn = self.db.begin().await?;
+        Novel::delete_by_id(info.id).exec(&txn).await?;
+        model.insert(&txn).await?;
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    pub(crate) async fn find_volume_infos(
+        &self,
+        id: u32,
+        freshness: Freshness,
+    ) -> Result<Option<VolumeInfos>, Error> {
+        let volumes = Volume::find()
+            .filter(entity::volume::Column::NovelId.eq(id))
+            .order_by_asc(entity::volume::Column::Position)
+            .all(&self.db)
+            .await?;
+
+        if volumes.is_empty() || !freshness.is_fresh(volumes[0].fetched_at) {
+            return Ok(None);
+        }
+
+        let chapters = Chapter::find()
+            .filter(entity::chapter::Column::NovelId.eq(id))
+            .order_by_asc(entity::chapter::Column::VolumePosition)
+            .order_by_asc(entity::chapter::Column::Position)
+            .all(&self.db)
+            .await?;
+
+        // Whether a chapter has been bought depends on the account, the chapters saved by
+        // another account of a shared database are fetched again
+        let payments: HashMap<u32, Option<bool>> = ChapterPayment::find()
+            .filter(entity::chapter_payment::Column::Account.eq(self.account()))
+            .filter(entity::chapter_payment::Column::NovelId.eq(id))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|payment| (payment.chapter_id, payment.payment_required))
+            .collect();
+        if chapters
+            .iter()
+            .any(|chapter| !payments.contains_key(&chapter.id))
+        {
+            return Ok(None);
+        }
+
+        let mut volume_infos: VolumeInfos = volumes
+            .into_iter()
+            .map(|volume| VolumeInfo {
+                title: volume.title,
+                chapter_infos: Vec::new(),
+            })
+            .collect();
+
+        for chapter in chapters {
+            if let Some(volume_info) = volume_infos.get_mut(chapter.volume_position as usize) {
+                volume_info.chapter_infos.push(ChapterInfo {
+                    novel_id: Some(chapter.novel_id),
+                    id: chapter.id,
+                    title: chapter.title,
+                    is_vip: chapter.is_vip,
+                    price: chapter.price,
+                    payment_required: payments[&chapter.id],
+                    is_valid: chapter.is_valid,
+                    word_count: chapter.word_count,
+                    create_time: chapter.create_time,
+                    update_time: chapter.update_time,
+                });
+            }
+        }
+
+        Ok(Some(volume_infos))
+    }
+
+    pub(crate) async fn save_volume_infos(
+        &self,
+        id: u32,
+        volume_infos: &VolumeInfos,
+    ) -> Result<(), Error> {
+        let fetched_at = Utc::now().naive_utc();
+
+        let mut volumes = Vec::with_capacity(volume_infos.len());
+        let mut chapters = Vec::new();
+        let mut payments = Vec::new();
+
+        for (volume_position, volume_info) in volume_infos.iter().enumerate() {
+            volumes.push(entity::volume::ActiveModel {
+                novel_id: sea_orm::Set(id),
+                position: sea_orm::Set(volume_position as u32),
+                title: sea_orm::Set(volume_info.title.clone()),
+                fetched_at: sea_orm::Set(fetched_at),
+            });
+
+            for (position, chapter_info) in volume_info.chapter_infos.iter().enumerate() {
+                chapters.push(entity::chapter::ActiveModel {
+                    novel_id: sea_orm::Set(id),
+                    volume_position: sea_orm::Set(volume_position as u32),
+                    position: sea_orm::Set(position as u32),
+                    id: sea_orm::Set(chapter_info.id),
+                    title: sea_orm::Set(chapter_info.title.clone()),
+                    is_vip: sea_orm::Set(chapter_info.is_vip),
+                    price: sea_orm::Set(chapter_info.price),
+                    is_valid: sea_orm::Set(chapter_info.is_valid),
+                    word_count: sea_orm::Set(chapter_info.word_count),
+                    create_time: sea_orm::Set(chapter_info.create_time),
+                    update_time: sea_orm::Set(chapter_info.update_time),
+                });
+                payments.push(entity::chapter_payment::ActiveModel {
+                    account: sea_orm::Set(self.account()),
+                    chapter_id: sea_orm::Set(chapter_info.id),
+                    novel_id: sea_orm::Set(id),
+                    payment_required: sea_orm::Set(chapter_info.payment_required),
+                });
+            }
+        }
+
+        let txn = self.db.begin().await?;
+
+        Volume::delete_many()
+            .filter(entity::volume::Column::NovelId.eq(id))
+            .exec(&txn)
+            .await?;
+        Chapter::delete_many()
+            .filter(entity::chapter::Column::NovelId.eq(id))
+            .exec(&txn)
+            .await?;
+        ChapterPayment::delete_many()
+            .filter(entity::chapter_payment::Column::Account.eq(self.account()))
+            .filter(entity::chapter_payment::Column::NovelId.eq(id))
+            .exec(&txn)
+            .await?;
+
+        for chunk in volumes.chunks(NovelDB::INSERT_CHUNK_SIZE) {
+            Volume::insert_many(chunk.to_vec()).exec(&txn).await?;
+        }
+        for chunk in chapters.chunks(NovelDB::INSERT_CHUNK_SIZE) {
+            Chapter::insert_many(chunk.to_vec()).exec(&txn).await?;
+        }
+        for chunk in payments.chunks(NovelDB::INSERT_CHUNK_SIZE) {
+            ChapterPayment::insert_many(chunk.to_vec())
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Keep the saved volume information of the account in line after a chapter is bought
+    pub(crate) async fn set_chapter_paid(&self, id: u32) -> Result<(), Error> {
+        ChapterPayment::update_many()
+            .col_expr(
+                entity::chapter_payment::Column::PaymentRequired,
+                Expr::value(false),
+            )
+            .filter(entity::chapter_payment::Column::Account.eq(self.account()))
+            .filter(entity::chapter_payment::Column::ChapterId.eq(id))
+            .exec(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Save the bought chapters to the audit log of the account
+    pub(crate) async fn insert_purchases(&self, infos: &[&ChapterInfo]) -> Result<(), Error> {
+        let purchased_at = Utc::now().naive_utc();
+
+        let purchases = infos
+            .iter()
+            .map(|info| entity::purchase::ActiveModel {
+                account: sea_orm::Set(self.cache_namespace.clone()),
+                novel_id: sea_orm::Set(info.novel_id),
+                chapter_id: sea_orm::Set(info.id),
+                price: sea_orm::Set(info.price),
+                purchased_at: sea_orm::Set(purchased_at),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let txn = self.db.begin().await?;
+        for chunk in purchases.chunks(NovelDB::INSERT_CHUNK_SIZE) {
+            Purchase::insert_many(chunk.to_vec()).exec(&txn).await?;
+        }
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Get the purchases of the account, oldest first
+    pub(crate) async fn find_purchases(&self) -> Result<Vec<PurchaseRecord>, Error> {
+        let purchases = Purchase::find()
+            .filter(self.account_filter())
+            .order_by_asc(entity::purchase::Column::Id)
+            .all(&self.db)
+            .await?;
+
+        Ok(purchases
+            .into_iter()
+            .map(|model| PurchaseRecord {
+                novel_id: model.novel_id,
+                chapter_id: model.chapter_id,
+                price: model.price,
+                purchased_at: model.purchased_at,
+            })
+            .collect())
+    }
+
+    /// Get the amount the account has spent on the novel
+    pub(crate) async fn find_novel_spending(&self, novel_id: u32) -> Result<u32, Error> {
+        let purchases = Purchase::find()
+            .filter(self.account_filter())
+            .filter(entity::purchase::Column::NovelId.eq(novel_id))
+            .all(&self.db)
+            .await?;
+
+        Ok(purchases
+            .into_iter()
+            .map(|model| model.price.unwrap_or_default() as u32)
+            .sum())
+    }
+
+    pub(crate) async fn find_cache<T>(&self, key: CacheKey) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let key = self.cache_key(key);
+
+        match Cache::find_by_id(&key).one(&self.db).await? {
+            Some(model) => Ok(serde_json::from_str(&model.value)?),
+            None => Err(Error::NotCached(format!("`{key}`"))),
+        }
+    }
+
+    pub(crate) async fn save_cache<T>(&self, key: CacheKey, value: &T) -> Result<(), Error>
+    where
+        T: Serialize,
+    {
+        let key = self.cache_key(key);
+        let model = entity::cache::ActiveModel {
+            key: sea_orm::Set(key.clone()),
+            value: sea_orm::Set(serde_json::to_string(value)?),
+            fetched_at: sea_orm::Set(Utc::now().naive_utc()),
+        };
+
+        let txn = self.db.begin().await?;
+        Cache::delete_by_id(key).exec(&txn).await?;
+        model.insert(&txn).await?;
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Key of the per-account rows, the default profile uses an empty string
+    fn account(&self) -> String {
+        self.cache_namespace.clone().unwrap_or_default()
+    }
+
+    fn account_filter(&self) -> sea_orm::sea_query::SimpleExpr {
+        match &self.cache_namespace {
+            Some(namespace) => entity::purchase::Column::Account.eq(namespace.as_str()),
+            None => entity::purchase::Column::Account.is_null(),
+        }
+    }
+
+    fn cache_key(&self, key: CacheKey) -> String {
+        match &self.cache_namespace {
+            Some(namespace) if key.per_account() => format!("{namespace}/{}", key.as_str()),
+            _ => key.as_str().to_string(),
+        }
+    }
+
+    fn db_path(app_name: &str, profile: &Profile, storage: &Storage) -> Result<PathBuf, Error> {
+        let mut db_path = profile.data_dir(app_name, storage)?;
         db_path.push(NovelDB::DB_NAME);
 
         Ok(db_path)
@@ -179,6 +551,20 @@ impl NovelDB {
     }
 }
 
+fn to_json<T>(value: &Option<T>) -> Result<Option<String>, Error>
+where
+    T: Serialize,
+{
+    Ok(value.as_ref().map(serde_json::to_string).transpose()?)
+}
+
+fn from_json<T>(json: Option<String>) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    Ok(json.map(|json| serde_json::from_str(&json)).transpose()?)
+}
+
 async fn zstd_decompress<T>(data: T) -> Result<Vec<u8>, Error>
 where
     T: AsRef<[u8]>,
@@ -211,6 +597,7 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::Tag;
 
     #[tokio::test]
     async fn zstd() -> Result<(), Error> {
@@ -229,7 +616,8 @@ mod tests {
         let app_name = "test-app";
         let contents = "test-contents";
 
-        let db = NovelDB::new(app_name).await?;
+        let dir = tempfile::tempdir()?;
+        let db = NovelDB::new(app_name, &Profile::default(), &Storage::root(dir.path())).await?;
 
         let chapter_info_old = ChapterInfo {
             id: 0,
@@ -263,4 +651,100 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn metadata() -> Result<(), Error> {
+        let app_name = "test-app-metadata";
+
+        let dir = tempfile::tempdir()?;
+        let db = NovelDB::new(app_name, &Profile::default(), &Storage::root(dir.path())).await?;
+
+        let novel_info = NovelInfo {
+            id: 1,
+            name: String::from("test-novel"),
+            author_id: Some(3),
+            introduction: Some(vec![String::from("line-1"), String::from("line-2")]),
+            tags: Some(vec![Tag {
+                id: Some(2),
+                name: String::from("test-tag"),
+            }]),
+            ..Default::default()
+        };
+
+        db.save_novel_info(&novel_info).await?;
+        assert!(db.find_novel_info(1, Freshness::NoCache).await?.is_none());
+
+        let saved = db.find_novel_info(1, Freshness::Forever).await?.unwrap();
+        assert_eq!(saved.name, novel_info.name);
+        assert_eq!(saved.author_id, novel_info.author_id);
+        assert_eq!(saved.introduction, novel_info.introduction);
+        assert_eq!(saved.tags, novel_info.tags);
+
+        let volume_infos = vec![
+            VolumeInfo {
+                title: String::from("volume-1"),
+                chapter_infos: vec![ChapterInfo {
+                    id: 10,
+                    payment_required: Some(true),
+                    ..Default::default()
+                }],
+            },
+            VolumeInfo {
+                title: String::from("volume-2"),
+                chapter_infos: Vec::new(),
+            },
+        ];
+
+        db.save_volume_infos(1, &volume_infos).await?;
+        db.set_chapter_paid(10).await?;
+
+        let saved = db
+            .find_volume_infos(1, Freshness::MaxAge(Duration::from_secs(60)))
+            .await?
+            .unwrap();
+        assert_eq!(saved.len(), 2);
+        assert_eq!(saved[1].title, "volume-2");
+        assert_eq!(saved[0].chapter_infos[0].id, 10);
+        assert!(saved[0].chapter_infos[0].can_download());
+
+        db.drop().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn shared_payment() -> Result<(), Error> {
+        let app_name = "test-app-shared-payment";
+
+        let dir = tempfile::tempdir()?;
+        let storage = Storage::root(dir.path());
+        let db_a = NovelDB::new(app_name, &Profile::new("account-a")?, &storage).await?;
+        let db_b = NovelDB::new(app_name, &Profile::new("account-b")?, &storage).await?;
+
+        let volume_infos = vec![VolumeInfo {
+            title: String::from("volume-1"),
+            chapter_infos: vec![ChapterInfo {
+                id: 10,
+                payment_required: Some(true),
+                ..Default::default()
+            }],
+        }];
+        let freshness = Freshness::MaxAge(Duration::from_secs(60));
+
+        db_a.save_volume_infos(1, &volume_infos).await?;
+        // The payment state of account A is not used for account B
+        assert!(db_b.find_volume_infos(1, freshness).await?.is_none());
+
+        db_b.save_volume_infos(1, &volume_infos).await?;
+        db_a.set_chapter_paid(10).await?;
+
+        let saved = db_a.find_volume_infos(1, freshness).await?.unwrap();
+        assert!(saved[0].chapter_infos[0].can_download());
+        let saved = db_b.find_volume_infos(1, freshness).await?.unwrap();
+        assert!(!saved[0].chapter_infos[0].can_download());
+
+        db_a.drop().await?;
+
+        Ok(())
+    }
 }
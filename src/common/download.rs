@@ -0,0 +1,221 @@
+use std::{collections::HashMap, sync::Arc};
+
+use tokio::{
+    sync::{mpsc, Semaphore},
+    task::{JoinHandle, JoinSet},
+};
+use tracing::info;
+
+use crate::{ChapterInfo, Client, ContentInfo, ContentInfos, Error, VolumeInfos};
+
+// Number of events that can wait to be received, the download pauses when it is full
+const EVENT_BUFFER_SIZE: usize = 64;
+
+/// Options used by the bulk download
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// Maximum number of chapters downloaded at the same time
+    pub concurrency: usize,
+    /// Whether to download the images in the chapters
+    pub images: bool,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            images: true,
+        }
+    }
+}
+
+/// Progress of the bulk download
+#[derive(Debug)]
+pub enum DownloadEvent {
+    /// The volume information has been fetched
+    Started {
+        /// Volume information of the novel
+        volume_infos: VolumeInfos,
+    },
+    /// Start downloading a chapter
+    ChapterStarted {
+        /// Chapter id
+        chapter_id: u32,
+    },
+    /// The chapter and its images have been downloaded
+    ChapterFinished {
+        /// Chapter id
+        chapter_id: u32,
+        /// Chapter content
+        content_infos: ContentInfos,
+    },
+    /// The chapter can not be downloaded, see `ChapterInfo::can_download`
+    ChapterSkipped {
+        /// Chapter id
+        chapter_id: u32,
+    },
+    /// Failed to download the chapter or one of its images, or its task panicked
+    ChapterFailed {
+        /// Chapter id
+        chapter_id: u32,
+        /// The reason of the failure
+        error: Error,
+    },
+}
+
+/// A running bulk download, it is cancelled when dropped
+#[must_use]
+pub struct Download {
+    events: mpsc::Receiver<DownloadEvent>,
+    handle: JoinHandle<Result<(), Error>>,
+}
+
+impl Download {
+    /// Receive the next event, `None` is returned when the download is over
+    pub async fn recv(&mut self) -> Option<DownloadEvent> {
+        self.events.recv().await
+    }
+
+    /// Stop downloading, the chapters already downloaded are kept in the database
+    pub fn cancel(&self) {
+        self.handle.abort();
+    }
+
+    /// Wait for the download to finish, events that have not been received are discarded
+    pub async fn wait(mut self) -> Result<(), Error> {
+        self.events.close();
+
+        match (&mut self.handle).await {
+            Ok(result) => result,
+            Err(err) if err.is_cancelled() => Err(Error::Cancelled),
+            Err(err) => Err(Error::NovelApi(err.to_string())),
+        }
+    }
+}
+
+impl Drop for Download {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Download all chapters of the novel and their images, chapters that can not be downloaded are skipped
+pub fn download_novel<C>(client: Arc<C>, id: u32, options: DownloadOptions) -> Download
+where
+    C: Client + Send + Sync + 'static,
+{
+    let (tx, rx) = mpsc::channel(EVENT_BUFFER_SIZE);
+
+    Download {
+        events: rx,
+        handle: tokio::spawn(run(client, id, options, tx)),
+    }
+}
+
+async fn run<C>(
+    client: Arc<C>,
+    id: u32,
+    options: DownloadOptions,
+    tx: mpsc::Sender<DownloadEvent>,
+) -> Result<(), Error>
+where
+    C: Client + Send + Sync + 'static,
+{
+    let Some(volume_infos) = client.volume_infos(id).await? else {
+        return Err(Error::NotFound(format!("novel {id}")));
+    };
+
+    let chapter_infos: Vec<ChapterInfo> = volume_infos
+        .iter()
+        .flat_map(|volume_info| volume_info.chapter_infos.iter().cloned())
+        .collect();
+    info!(
+        "Start downloading novel `{id}`, number of chapters: `{}`",
+        chapter_infos.len()
+    );
+
+    tx.send(DownloadEvent::Started { volume_infos }).await.ok();
+
+    let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+    let mut tasks = JoinSet::new();
+    let mut chapter_ids = HashMap::new();
+
+    for chapter_info in chapter_infos {
+        if !chapter_info.can_download() {
+            tx.send(DownloadEvent::ChapterSkipped {
+                chapter_id: chapter_info.id,
+            })
+            .await
+            .ok();
+            continue;
+        }
+
+        let permit = Arc::clone(&semaphore).acquire_owned().await.unwrap();
+        let client = Arc::clone(&client);
+        let tx = tx.clone();
+
+        let chapter_id = chapter_info.id;
+        let task = tasks.spawn(async move {
+            tx.send(DownloadEvent::ChapterStarted {
+                chapter_id: chapter_info.id,
+            })
+            .await
+            .ok();
+
+            let event = match download_chapter(&*client, &chapter_info, options.images).await {
+                Ok(content_infos) => DownloadEvent::ChapterFinished {
+                    chapter_id: chapter_info.id,
+                    content_infos,
+                },
+                Err(error) => DownloadEvent::ChapterFailed {
+                    chapter_id: chapter_info.id,
+                    error,
+                },
+            };
+            tx.send(event).await.ok();
+
+            drop(permit);
+        });
+        chapter_ids.insert(task.id(), chapter_id);
+    }
+
+    while let Some(result) = tasks.join_next().await {
+        if let Err(err) = result {
+            let error = if err.is_cancelled() {
+                Error::Cancelled
+            } else {
+                Error::NovelApi(err.to_string())
+            };
+
+            tx.send(DownloadEvent::ChapterFailed {
+                chapter_id: chapter_ids[&err.id()],
+                error,
+            })
+            .await
+            .ok();
+        }
+    }
+
+    Ok(())
+}
+
+async fn download_chapter<C>(
+    client: &C,
+    chapter_info: &ChapterInfo,
+    images: bool,
+) -> Result<ContentInfos, Error>
+where
+    C: Client + Send + Sync,
+{
+    let content_infos = client.content_infos(chapter_info).await?;
+
+    if images {
+        for content_info in &content_infos {
+            if let ContentInfo::Image(url) = content_info {
+                client.image(url).await?;
+            }
+        }
+    }
+
+    Ok(content_infos)
+}
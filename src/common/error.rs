@@ -45,6 +45,10 @@ pub enum Error {
     InvalidHeaderValue(#[from] http::header::InvalidHeaderValue),
     #[error(transparent)]
     Dialoguer(#[from] dialoguer::Error),
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+    #[error(transparent)]
+    Askama(#[from] askama::Error),
     #[error("{0}")]
     Ring(String),
     #[error("{0}")]
@@ -53,6 +57,54 @@ pub enum Error {
     Port(String),
     #[error("{0}")]
     NovelApi(String),
+    #[error("{0}")]
+    Cassette(String),
+    #[error("The client options are invalid: {0}")]
+    InvalidOptions(String),
+    #[error("The client is offline and {0} is not cached")]
+    NotCached(String),
+    #[error("The download was cancelled")]
+    Cancelled,
     #[error("The HTTP request failed, status code: `{code}`, message: `{msg}`")]
     Http { code: StatusCode, msg: String },
+    #[error("The login has expired, please log in again")]
+    LoginExpired,
+    #[error("The resource does not exist: `{0}`")]
+    NotFound(String),
+    #[error("The balance is insufficient: `{0}`")]
+    InsufficientBalance(String),
+    #[error("The chapter has not been purchased: `{0}`")]
+    ChapterLocked(String),
+    #[error("Too many requests, please try again later: `{0}`")]
+    RateLimited(String),
+    #[error("Captcha verification is required: `{0}`")]
+    CaptchaRequired(String),
+    #[error("The spending budget would be exceeded: `{0}`")]
+    BudgetExceeded(String),
+    #[error("The source does not support it: `{0}`")]
+    Unsupported(String),
+    #[error("The keyring can not store the file encryption key, use a passphrase key source instead: `{0}`")]
+    KeyringUnavailable(keyring::Error),
+    #[error("The server returned an error, code: `{code}`, message: `{msg}`")]
+    ServerError { code: String, msg: String },
+}
+
+impl Error {
+    /// Classify a failed API response by its message, used when the code is not specific
+    pub(crate) fn from_server(code: String, msg: String) -> Self {
+        let msg = msg.trim().to_string();
+
+        if msg.contains("余额不足") || msg.contains("余额不够") {
+            Self::InsufficientBalance(msg)
+        } else if msg.contains("未购买") || msg.contains("未订阅") || msg.contains("请先购买")
+        {
+            Self::ChapterLocked(msg)
+        } else if msg.contains("频繁") {
+            Self::RateLimited(msg)
+        } else if msg.contains("验证码") {
+            Self::CaptchaRequired(msg)
+        } else {
+            Self::ServerError { code, msg }
+        }
+    }
 }
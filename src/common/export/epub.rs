@@ -0,0 +1,332 @@
+use std::{
+    collections::HashMap,
+    io::{Cursor, Write},
+    path::Path,
+};
+
+use askama::Template;
+use chrono::{SecondsFormat, Utc};
+use image::{DynamicImage, ImageFormat};
+use tracing::{error, info};
+use url::Url;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{Client, ContentInfo, Error, NovelInfo, VolumeInfos};
+
+const CONTAINER: &str = include_str!("../../../templates/epub/container.xml");
+const STYLE: &str = include_str!("../../../templates/epub/style.css");
+const XHTML: &str = "application/xhtml+xml";
+const JPEG: &str = "image/jpeg";
+
+#[derive(Template)]
+#[template(path = "epub/content.opf", escape = "html")]
+struct PackageTemplate<'a> {
+    identifier: String,
+    title: &'a str,
+    author: &'a str,
+    description: Option<String>,
+    subjects: Vec<&'a str>,
+    modified: String,
+    has_cover: bool,
+    items: &'a [ManifestItem],
+}
+
+struct ManifestItem {
+    id: String,
+    href: String,
+    media_type: &'static str,
+    properties: Option<&'static str>,
+    in_spine: bool,
+}
+
+#[derive(Template)]
+#[template(path = "epub/nav.xhtml", escape = "html")]
+struct NavTemplate<'a> {
+    title: &'a str,
+    volumes: Vec<NavVolume<'a>>,
+}
+
+struct NavVolume<'a> {
+    title: &'a str,
+    href: String,
+    chapters: Vec<NavChapter<'a>>,
+}
+
+struct NavChapter<'a> {
+    title: &'a str,
+    href: String,
+}
+
+#[derive(Template)]
+#[template(path = "epub/page.xhtml", escape = "html")]
+struct PageTemplate<'a> {
+    title: &'a str,
+    heading: Option<u8>,
+    paragraphs: Vec<Paragraph>,
+}
+
+enum Paragraph {
+    Text(String),
+    Image(String),
+}
+
+/// Export the novel as an EPUB 3 file, chapters that can not be downloaded are skipped
+///
+/// The contents and images are fetched by the client, so an offline client exports
+/// the novel from the database only
+pub async fn export_epub<C, T>(
+    client: &C,
+    novel_info: &NovelInfo,
+    volume_infos: &VolumeInfos,
+    path: T,
+) -> Result<(), Error>
+where
+    C: Client,
+    T: AsRef<Path>,
+{
+    let mut epub = Epub::new()?;
+
+    let has_cover = match &novel_info.cover_url {
+        Some(url) => match client.image(url).await {
+            Ok(image) => {
+                epub.add_file(
+                    "OEBPS/images/cover.jpg",
+                    &encode_jpeg(&image)?,
+                    CompressionMethod::Stored,
+                )?;
+                epub.items.push(ManifestItem {
+                    id: String::from("cover-image"),
+                    href: String::from("images/cover.jpg"),
+                    media_type: JPEG,
+                    properties: Some("cover-image"),
+                    in_spine: false,
+                });
+
+                let page = PageTemplate {
+                    title: &novel_info.name,
+                    heading: None,
+                    paragraphs: vec![Paragraph::Image(String::from("../images/cover.jpg"))],
+                };
+                epub.add_page("cover", &page)?;
+
+                true
+            }
+            Err(err) => {
+                error!("Failed to get the cover image, it is not exported: {err}");
+                false
+            }
+        },
+        None => false,
+    };
+
+    if let Some(introduction) = &novel_info.introduction {
+        let page = PageTemplate {
+            title: "简介",
+            heading: Some(1),
+            paragraphs: introduction.iter().cloned().map(Paragraph::Text).collect(),
+        };
+        epub.add_page("introduction", &page)?;
+    }
+
+    let mut nav_volumes = Vec::with_capacity(volume_infos.len());
+    let mut images: HashMap<Url, String> = HashMap::new();
+
+    for (volume_index, volume_info) in volume_infos.iter().enumerate() {
+        let volume_id = format!("volume-{}", volume_index + 1);
+        let page = PageTemplate {
+            title: &volume_info.title,
+            heading: Some(1),
+            paragraphs: Vec::new(),
+        };
+        epub.add_page(&volume_id, &page)?;
+
+        let mut nav_chapters = Vec::new();
+
+        for chapter_info in &volume_info.chapter_infos {
+            if !chapter_info.can_download() {
+                info!(
+                    "Skip the chapter that can not be downloaded: `{}`",
+                    chapter_info.id
+                );
+                continue;
+            }
+
+            let mut paragraphs = Vec::new();
+            for content_info in client.content_infos(chapter_info).await? {
+                match content_info {
+                    ContentInfo::Text(text) => paragraphs.push(Paragraph::Text(text)),
+                    ContentInfo::Image(url) => {
+                        let href = match images.get(&url) {
+                            Some(href) => href.clone(),
+                            None => {
+                                let name = format!("{}.jpg", images.len() + 1);
+                                let image = client.image(&url).await?;
+
+                                epub.add_file(
+                                    &format!("OEBPS/images/{name}"),
+                                    &encode_jpeg(&image)?,
+                                    CompressionMethod::Stored,
+                                )?;
+                                epub.items.push(ManifestItem {
+                                    id: format!("image-{}", images.len() + 1),
+                                    href: format!("images/{name}"),
+                                    media_type: JPEG,
+                                    properties: None,
+                                    in_spine: false,
+                                });
+
+                                let href = format!("../images/{name}");
+                                images.insert(url, href.clone());
+                                href
+                            }
+                        };
+                        paragraphs.push(Paragraph::Image(href));
+                    }
+                }
+            }
+
+            let chapter_id = format!("chapter-{}", chapter_info.id);
+            let page = PageTemplate {
+                title: &chapter_info.title,
+                heading: Some(2),
+                paragraphs,
+            };
+            epub.add_page(&chapter_id, &page)?;
+
+            nav_chapters.push(NavChapter {
+                title: &chapter_info.title,
+                href: format!("text/{chapter_id}.xhtml"),
+            });
+        }
+
+        nav_volumes.push(NavVolume {
+            title: &volume_info.title,
+            href: format!("text/{volume_id}.xhtml"),
+            chapters: nav_chapters,
+        });
+    }
+
+    let nav = NavTemplate {
+        title: &novel_info.name,
+        volumes: nav_volumes,
+    };
+    epub.add_file(
+        "OEBPS/nav.xhtml",
+        nav.render()?.as_bytes(),
+        CompressionMethod::Deflated,
+    )?;
+    epub.add_file(
+        "OEBPS/style.css",
+        STYLE.as_bytes(),
+        CompressionMethod::Deflated,
+    )?;
+
+    let mut subjects = Vec::new();
+    if let Some(category) = &novel_info.category {
+        subjects.push(category.name.as_str());
+    }
+    if let Some(tags) = &novel_info.tags {
+        subjects.extend(tags.iter().map(|tag| tag.name.as_str()));
+    }
+
+    let package = PackageTemplate {
+        identifier: format!("urn:novel-rs:{}", novel_info.id),
+        title: &novel_info.name,
+        author: &novel_info.author_name,
+        description: novel_info
+            .introduction
+            .as_ref()
+            .map(|introduction| introduction.join("\n")),
+        subjects,
+        modified: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        has_cover,
+        items: &epub.items,
+    };
+    let package = package.render()?;
+    epub.add_file(
+        "OEBPS/content.opf",
+        package.as_bytes(),
+        CompressionMethod::Deflated,
+    )?;
+
+    let path = path.as_ref();
+    tokio::fs::write(path, epub.finish()?).await?;
+    info!(
+        "Export the novel `{}` to: `{}`",
+        novel_info.id,
+        path.display()
+    );
+
+    Ok(())
+}
+
+struct Epub {
+    zip: ZipWriter<Cursor<Vec<u8>>>,
+    items: Vec<ManifestItem>,
+}
+
+impl Epub {
+    fn new() -> Result<Self, Error> {
+        let mut epub = Self {
+            zip: ZipWriter::new(Cursor::new(Vec::new())),
+            items: Vec::new(),
+        };
+
+        // The mimetype file must be the first entry and must not be compressed
+        epub.add_file(
+            "mimetype",
+            b"application/epub+zip",
+            CompressionMethod::Stored,
+        )?;
+        epub.add_file(
+            "META-INF/container.xml",
+            CONTAINER.as_bytes(),
+            CompressionMethod::Deflated,
+        )?;
+
+        Ok(epub)
+    }
+
+    fn add_file(
+        &mut self,
+        name: &str,
+        bytes: &[u8],
+        compression_method: CompressionMethod,
+    ) -> Result<(), Error> {
+        let options = SimpleFileOptions::default().compression_method(compression_method);
+
+        self.zip.start_file(name, options)?;
+        self.zip.write_all(bytes)?;
+
+        Ok(())
+    }
+
+    fn add_page(&mut self, id: &str, page: &PageTemplate<'_>) -> Result<(), Error> {
+        self.add_file(
+            &format!("OEBPS/text/{id}.xhtml"),
+            page.render()?.as_bytes(),
+            CompressionMethod::Deflated,
+        )?;
+        self.items.push(ManifestItem {
+            id: id.to_string(),
+            href: format!("text/{id}.xhtml"),
+            media_type: XHTML,
+            properties: None,
+            in_spine: true,
+        });
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Vec<u8>, Error> {
+        Ok(self.zip.finish()?.into_inner())
+    }
+}
+
+fn encode_jpeg(image: &DynamicImage) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    DynamicImage::ImageRgb8(image.to_rgb8())
+        .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Jpeg)?;
+
+    Ok(bytes)
+}
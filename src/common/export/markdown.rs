@@ -0,0 +1,120 @@
+use std::path::Path;
+
+use super::Layout;
+use crate::{Client, Error, ExportOptions, NovelInfo, VolumeInfos};
+
+/// Export the novel as a Markdown file, chapters that can not be downloaded are skipped
+///
+/// The metadata is written as a YAML front matter that pandoc understands
+pub async fn export_markdown<C, T>(
+    client: &C,
+    novel_info: &NovelInfo,
+    volume_infos: &VolumeInfos,
+    path: T,
+    options: &ExportOptions,
+) -> Result<(), Error>
+where
+    C: Client,
+    T: AsRef<Path>,
+{
+    super::export_text(
+        client,
+        novel_info,
+        volume_infos,
+        path.as_ref(),
+        options,
+        Markdown,
+    )
+    .await
+}
+
+struct Markdown;
+
+impl Layout for Markdown {
+    fn front_matter(&self, output: &mut String, novel_info: &NovelInfo, cover: Option<&str>) {
+        output.push_str("---\n");
+        push_field(output, "title", &novel_info.name);
+        push_field(output, "author", &novel_info.author_name);
+        if let Some(introduction) = &novel_info.introduction {
+            push_field(output, "description", &introduction.join("\n"));
+        }
+        if let Some(category) = &novel_info.category {
+            push_field(output, "subject", &category.name);
+        }
+        if let Some(tags) = novel_info.tags.as_ref().filter(|tags| !tags.is_empty()) {
+            output.push_str("keywords:\n");
+            for tag in tags {
+                output.push_str("  - ");
+                output.push_str(&yaml_string(&tag.name));
+                output.push('\n');
+            }
+        }
+        if let Some(update_time) = novel_info.update_time {
+            push_field(output, "date", &update_time.to_string());
+        }
+        if let Some(cover) = cover {
+            push_field(output, "cover-image", cover);
+        }
+        push_field(output, "lang", "zh-CN");
+        output.push_str("---\n\n");
+    }
+
+    fn introduction(&self, output: &mut String, introduction: &[String]) {
+        if introduction.is_empty() {
+            return;
+        }
+
+        let lines: Vec<String> = introduction
+            .iter()
+            .map(|line| format!("> {}", escape(line)))
+            .collect();
+        output.push_str(&lines.join("\n>\n"));
+        output.push_str("\n\n");
+    }
+
+    fn heading(&self, output: &mut String, level: u8, title: &str) {
+        output.push_str(&"#".repeat(level.clamp(1, 6) as usize));
+        output.push(' ');
+        output.push_str(&escape(title));
+        output.push_str("\n\n");
+    }
+
+    fn text(&self, output: &mut String, text: &str) {
+        output.push_str(&escape(text));
+        output.push_str("\n\n");
+    }
+
+    fn image(&self, output: &mut String, href: &str) {
+        output.push_str("![](");
+        output.push_str(&href.replace(' ', "%20"));
+        output.push_str(")\n\n");
+    }
+}
+
+fn push_field(output: &mut String, key: &str, value: &str) {
+    output.push_str(key);
+    output.push_str(": ");
+    output.push_str(&yaml_string(value));
+    output.push('\n');
+}
+
+/// JSON strings are valid YAML flow scalars
+fn yaml_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap()
+}
+
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~'
+        ) {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+
+    result
+}
@@ -0,0 +1,195 @@
+mod epub;
+mod markdown;
+mod txt;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use tracing::{error, info};
+use url::Url;
+
+pub use self::{epub::*, markdown::*, txt::*};
+use crate::{Client, ContentInfo, Error, NovelInfo, VolumeInfos};
+
+/// How the images are written by the Markdown and TXT exporters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageMode {
+    /// Save the images as WebP files in the `<file stem>-images` directory next to the exported file
+    #[default]
+    Local,
+    /// Link to the remote image URLs
+    Remote,
+}
+
+/// Options used by the Markdown and TXT exporters
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Heading level of the volume titles, only used by Markdown
+    pub volume_heading_level: u8,
+    /// Heading level of the chapter titles, only used by Markdown
+    pub chapter_heading_level: u8,
+    /// How the images are written
+    pub images: ImageMode,
+    /// Whether to include the novel introduction
+    pub introduction: bool,
+    /// Whether to include the novel metadata at the beginning of the file
+    pub front_matter: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            volume_heading_level: 1,
+            chapter_heading_level: 2,
+            images: ImageMode::Local,
+            introduction: true,
+            front_matter: true,
+        }
+    }
+}
+
+const WEBP_QUALITY: f32 = 75.0;
+
+/// The syntax of a text based export format
+trait Layout {
+    fn front_matter(&self, output: &mut String, novel_info: &NovelInfo, cover: Option<&str>);
+    fn introduction(&self, output: &mut String, introduction: &[String]);
+    fn heading(&self, output: &mut String, level: u8, title: &str);
+    fn text(&self, output: &mut String, text: &str);
+    fn image(&self, output: &mut String, href: &str);
+}
+
+async fn export_text<C, L>(
+    client: &C,
+    novel_info: &NovelInfo,
+    volume_infos: &VolumeInfos,
+    path: &Path,
+    options: &ExportOptions,
+    layout: L,
+) -> Result<(), Error>
+where
+    C: Client,
+    L: Layout,
+{
+    let mut images = Images::new(path, options.images);
+    let mut output = String::new();
+
+    if options.front_matter {
+        let cover = match &novel_info.cover_url {
+            Some(url) => match images.href(client, url, Some("cover")).await {
+                Ok(href) => Some(href),
+                Err(err) => {
+                    error!("Failed to get the cover image, it is not exported: {err}");
+                    None
+                }
+            },
+            None => None,
+        };
+        layout.front_matter(&mut output, novel_info, cover.as_deref());
+    }
+
+    if options.introduction {
+        if let Some(introduction) = &novel_info.introduction {
+            layout.introduction(&mut output, introduction);
+        }
+    }
+
+    for volume_info in volume_infos {
+        layout.heading(
+            &mut output,
+            options.volume_heading_level,
+            &volume_info.title,
+        );
+
+        for chapter_info in &volume_info.chapter_infos {
+            if !chapter_info.can_download() {
+                info!(
+                    "Skip the chapter that can not be downloaded: `{}`",
+                    chapter_info.id
+                );
+                continue;
+            }
+
+            layout.heading(
+                &mut output,
+                options.chapter_heading_level,
+                &chapter_info.title,
+            );
+
+            for content_info in client.content_infos(chapter_info).await? {
+                match content_info {
+                    ContentInfo::Text(text) => layout.text(&mut output, &text),
+                    ContentInfo::Image(url) => {
+                        let href = images.href(client, &url, None).await?;
+                        layout.image(&mut output, &href);
+                    }
+                }
+            }
+        }
+    }
+
+    tokio::fs::write(path, output).await?;
+    info!(
+        "Export the novel `{}` to: `{}`",
+        novel_info.id,
+        path.display()
+    );
+
+    Ok(())
+}
+
+/// Resolve the image URLs to the links written in the exported file
+struct Images {
+    mode: ImageMode,
+    dir: PathBuf,
+    dir_name: String,
+    saved: HashMap<Url, String>,
+    count: usize,
+}
+
+impl Images {
+    fn new(path: &Path, mode: ImageMode) -> Self {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let dir_name = format!("{stem}-images");
+
+        Self {
+            mode,
+            dir: path.with_file_name(&dir_name),
+            dir_name,
+            saved: HashMap::new(),
+            count: 0,
+        }
+    }
+
+    async fn href<C>(&mut self, client: &C, url: &Url, name: Option<&str>) -> Result<String, Error>
+    where
+        C: Client,
+    {
+        if self.mode == ImageMode::Remote {
+            return Ok(url.to_string());
+        }
+
+        if let Some(href) = self.saved.get(url) {
+            return Ok(href.clone());
+        }
+
+        let image = client.image(url).await?;
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => {
+                self.count += 1;
+                self.count.to_string()
+            }
+        };
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        crate::save_as_webp(&image, WEBP_QUALITY, self.dir.join(format!("{name}.webp")))?;
+
+        let href = format!("{}/{name}.webp", self.dir_name);
+        self.saved.insert(url.clone(), href.clone());
+
+        Ok(href)
+    }
+}
@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use super::Layout;
+use crate::{Client, Error, ExportOptions, NovelInfo, VolumeInfos};
+
+/// Export the novel as a UTF-8 plain text file, chapters that can not be downloaded are skipped
+pub async fn export_txt<C, T>(
+    client: &C,
+    novel_info: &NovelInfo,
+    volume_infos: &VolumeInfos,
+    path: T,
+    options: &ExportOptions,
+) -> Result<(), Error>
+where
+    C: Client,
+    T: AsRef<Path>,
+{
+    super::export_text(
+        client,
+        novel_info,
+        volume_infos,
+        path.as_ref(),
+        options,
+        Txt,
+    )
+    .await
+}
+
+struct Txt;
+
+impl Layout for Txt {
+    fn front_matter(&self, output: &mut String, novel_info: &NovelInfo, cover: Option<&str>) {
+        push_field(output, "title", &novel_info.name);
+        push_field(output, "author", &novel_info.author_name);
+        if let Some(category) = &novel_info.category {
+            push_field(output, "category", &category.name);
+        }
+        if let Some(tags) = &novel_info.tags {
+            let tags: Vec<&str> = tags.iter().map(|tag| tag.name.as_str()).collect();
+            push_field(output, "tags", &tags.join(", "));
+        }
+        if let Some(word_count) = novel_info.word_count {
+            push_field(output, "word_count", &word_count.to_string());
+        }
+        if let Some(is_finished) = novel_info.is_finished {
+            push_field(output, "finished", &is_finished.to_string());
+        }
+        if let Some(update_time) = novel_info.update_time {
+            push_field(output, "update_time", &update_time.to_string());
+        }
+        if let Some(cover) = cover {
+            push_field(output, "cover", cover);
+        }
+        output.push('\n');
+    }
+
+    fn introduction(&self, output: &mut String, introduction: &[String]) {
+        for line in introduction {
+            output.push_str(line);
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+
+    fn heading(&self, output: &mut String, _: u8, title: &str) {
+        if !output.is_empty() && !output.ends_with("\n\n") {
+            output.push('\n');
+        }
+        output.push_str(title);
+        output.push_str("\n\n");
+    }
+
+    fn text(&self, output: &mut String, text: &str) {
+        output.push_str(text);
+        output.push('\n');
+    }
+
+    fn image(&self, output: &mut String, href: &str) {
+        output.push_str(href);
+        output.push('\n');
+    }
+}
+
+fn push_field(output: &mut String, key: &str, value: &str) {
+    output.push_str(key);
+    output.push_str(": ");
+    output.push_str(value);
+    output.push('\n');
+}
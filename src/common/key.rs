@@ -0,0 +1,108 @@
+use std::num::NonZeroU32;
+
+use ring::{
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use tracing::info;
+use zeroize::Zeroizing;
+
+use crate::{Error, Keyring};
+
+pub(crate) const KEY_LEN: usize = 32;
+pub(crate) const SALT_LEN: usize = 16;
+
+const KEYRING_USERNAME: &str = "file-encryption-key";
+#[cfg(not(test))]
+const PBKDF2_ITERATIONS: u32 = 600_000;
+#[cfg(test)]
+const PBKDF2_ITERATIONS: u32 = 1_000;
+
+/// Where the key that encrypts the config and cookie files comes from
+#[derive(Clone, Default)]
+pub enum KeySource {
+    /// A random key generated on first use and stored in the platform keyring,
+    /// the client fails to save its files if the keyring can not store it
+    #[default]
+    Keyring,
+    /// Derive the key from a passphrase with PBKDF2
+    Passphrase(Zeroizing<String>),
+}
+
+impl KeySource {
+    /// Derive the key from a passphrase
+    pub fn passphrase<T>(passphrase: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self::Passphrase(Zeroizing::new(passphrase.into()))
+    }
+}
+
+pub(crate) fn random_bytes<const N: usize>() -> Result<[u8; N], Error> {
+    let mut bytes = [0; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|error| Error::Ring(error.to_string()))?;
+
+    Ok(bytes)
+}
+
+/// The key of the encrypted files of `app_name`, `salt` is only used by passphrases
+pub(crate) fn file_key(
+    app_name: &str,
+    source: &KeySource,
+    salt: &[u8; SALT_LEN],
+) -> Result<Zeroizing<[u8; KEY_LEN]>, Error> {
+    match source {
+        KeySource::Keyring => keyring_key(app_name),
+        KeySource::Passphrase(passphrase) => {
+            let mut key = Zeroizing::new([0; KEY_LEN]);
+            pbkdf2::derive(
+                pbkdf2::PBKDF2_HMAC_SHA256,
+                NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+                salt,
+                passphrase.as_bytes(),
+                key.as_mut(),
+            );
+
+            Ok(key)
+        }
+    }
+}
+
+fn keyring_key(app_name: &str) -> Result<Zeroizing<[u8; KEY_LEN]>, Error> {
+    let keyring = Keyring::new(app_name, KEYRING_USERNAME)?;
+
+    match keyring.get_password() {
+        Ok(encoded) => return decode_key(encoded.as_bytes()),
+        Err(Error::Keyring(keyring::Error::NoEntry)) => (),
+        // A new key would make the files encrypted with the stored key unreadable
+        Err(error) => return Err(error),
+    }
+
+    let key = Zeroizing::new(random_bytes::<KEY_LEN>()?);
+    let encoded = base64_simd::STANDARD.encode_to_string(key.as_ref());
+
+    match keyring.set_password(encoded) {
+        Ok(()) => info!("A new file encryption key is stored in the keyring"),
+        Err(Error::Keyring(error)) => return Err(Error::KeyringUnavailable(error)),
+        Err(error) => return Err(error),
+    }
+
+    Ok(key)
+}
+
+fn decode_key(encoded: &[u8]) -> Result<Zeroizing<[u8; KEY_LEN]>, Error> {
+    let decoded = Zeroizing::new(base64_simd::STANDARD.decode_to_vec(encoded)?);
+
+    let mut key = Zeroizing::new([0; KEY_LEN]);
+    if decoded.len() != KEY_LEN {
+        return Err(Error::Ring(String::from(
+            "The file encryption key is invalid",
+        )));
+    }
+    key.copy_from_slice(&decoded);
+
+    Ok(key)
+}
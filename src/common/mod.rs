@@ -1,23 +1,53 @@
 mod aes;
+mod builder;
+mod cassette;
 mod client;
 mod config;
 mod database;
 mod des;
+mod download;
 mod error;
+mod export;
 mod hash;
+mod key;
 mod net;
+mod paragraph;
+mod profile;
+mod purchase;
+mod rate_limit;
+mod retry;
+mod search;
+mod spending;
+mod storage;
 mod uid;
 mod utils;
 
 pub(crate) mod date_format;
 pub(crate) mod date_format_option;
 
+pub use builder::ClientBuilder;
+pub(crate) use builder::ClientOptions;
+pub use cassette::*;
 pub use client::*;
 pub(crate) use config::*;
 pub(crate) use database::*;
+pub use download::*;
 pub use error::*;
+pub use export::*;
 pub(crate) use hash::*;
+pub use key::KeySource;
+pub(crate) use key::{file_key, random_bytes, SALT_LEN};
 pub(crate) use net::*;
+pub(crate) use paragraph::*;
+pub use profile::*;
+pub(crate) use purchase::*;
+pub use rate_limit::RateLimit;
+pub(crate) use rate_limit::RateLimiter;
+pub use retry::*;
+pub use search::*;
+pub(crate) use spending::SpendingGuard;
+pub use spending::SpendingPolicy;
+pub use storage::*;
 pub(crate) use uid::*;
 pub use utils::*;
 
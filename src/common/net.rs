@@ -1,7 +1,7 @@
 use std::{
     io::BufWriter,
     ops::Deref,
-    path::{Path, PathBuf},
+    path::PathBuf,
     sync::{Arc, RwLock},
     time::Duration,
 };
@@ -10,26 +10,30 @@ use bytes::Bytes;
 use cookie_store::{CookieStore, RawCookie, RawCookieParseError};
 use reqwest::{
     header::{HeaderMap, HeaderValue, IntoHeaderName, ACCEPT, CONNECTION},
-    redirect, Certificate, Client, Proxy, StatusCode,
+    redirect, Certificate, Client, Proxy, RequestBuilder, Response, StatusCode,
 };
 use tokio::fs;
 use tracing::{error, info};
 use url::Url;
 
-use crate::Error;
+use crate::{
+    Cassette, CassetteMode, CassetteRules, ClientOptions, Error, KeySource, Profile, RateLimit,
+    RateLimiter, RetryPolicy, Storage,
+};
 
 pub(crate) fn check_status<T>(code: StatusCode, msg: T) -> Result<(), Error>
 where
     T: AsRef<str>,
 {
-    if code != StatusCode::OK {
-        return Err(Error::Http {
-            code,
-            msg: msg.as_ref().trim().to_string(),
-        });
+    let msg = msg.as_ref().trim().to_string();
+
+    match code {
+        StatusCode::OK => Ok(()),
+        StatusCode::UNAUTHORIZED => Err(Error::LoginExpired),
+        StatusCode::NOT_FOUND => Err(Error::NotFound(msg)),
+        StatusCode::TOO_MANY_REQUESTS => Err(Error::RateLimited(msg)),
+        code => Err(Error::Http { code, msg }),
     }
-
-    Ok(())
 }
 
 #[must_use]
@@ -42,13 +46,24 @@ pub(crate) struct HTTPClientBuilder {
     proxy: Option<Url>,
     no_proxy: bool,
     cert_path: Option<PathBuf>,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
     headers: HeaderMap,
+    cassette: Option<Arc<Cassette>>,
+    cassette_rules: CassetteRules,
+    offline: bool,
+    retry_policy: RetryPolicy,
+    rate_limit: Option<RateLimit>,
+    profile: Profile,
+    storage: Storage,
+    key_source: KeySource,
 }
 
 impl HTTPClientBuilder {
-    const COOKIE_FILE_NAME: &'static str = "cookie.json";
+    pub(crate) const COOKIE_FILE_NAME: &'static str = "cookie.json";
 
-    const COOKIE_FILE_PASSWORD: &'static str = "gafqad-4Ratne-dirqom";
+    /// Only used to open the cookie files written by older versions
+    const LEGACY_COOKIE_FILE_PASSWORD: &'static str = "gafqad-4Ratne-dirqom";
     const COOKIE_FILE_AAD: &'static str = "novel-rs-cookie";
 
     pub(crate) fn new(app_name: &'static str) -> Self {
@@ -61,7 +76,17 @@ impl HTTPClientBuilder {
             proxy: None,
             no_proxy: false,
             cert_path: None,
-            headers: HeaderMap::new()
+            connect_timeout: None,
+            timeout: None,
+            headers: HeaderMap::new(),
+            cassette: None,
+            cassette_rules: CassetteRules::default(),
+            offline: false,
+            retry_policy: RetryPolicy::default(),
+            rate_limit: None,
+            profile: Profile::default(),
+            storage: Storage::default(),
+            key_source: KeySource::default(),
         }
     }
 
@@ -96,37 +121,46 @@ impl HTTPClientBuilder {
         }
     }
 
-    pub(crate) fn proxy(self, proxy: Option<Url>) -> Self {
-        Self { proxy, ..self }
+    pub(crate) fn add_header<K>(self, key: K, value: HeaderValue) -> Self
+    where
+        K: IntoHeaderName,
+    {
+        let mut result = self;
+        result.headers.append(key, value);
+
+        result
     }
 
-    pub(crate) fn no_proxy(self, flag: bool) -> Self {
+    pub(crate) fn cassette_rules(self, cassette_rules: CassetteRules) -> Self {
         Self {
-            no_proxy: flag,
+            cassette_rules,
             ..self
         }
     }
 
-    pub(crate) fn cert<T>(self, cert_path: Option<T>) -> Self
-    where
-        T: AsRef<Path>,
-    {
+    pub(crate) fn rate_limit(self, rate_limit: Option<RateLimit>) -> Self {
+        Self { rate_limit, ..self }
+    }
+
+    /// Apply the options of the client, the user agent is only replaced if it is set
+    pub(crate) fn options(self, options: &ClientOptions) -> Self {
         Self {
-            cert_path: cert_path.map(|path| path.as_ref().to_path_buf()),
+            user_agent: options.user_agent.clone().unwrap_or(self.user_agent),
+            proxy: options.proxy.clone(),
+            no_proxy: options.no_proxy,
+            cert_path: options.cert_path.clone(),
+            connect_timeout: options.connect_timeout,
+            timeout: options.timeout,
+            cassette: options.cassette.clone(),
+            offline: options.offline,
+            retry_policy: options.retry_policy.clone(),
+            profile: options.profile.clone(),
+            storage: options.storage.clone(),
+            key_source: options.key_source.clone(),
             ..self
         }
     }
 
-    pub(crate) fn add_header<K>(self, key: K, value: HeaderValue) -> Self
-    where
-        K: IntoHeaderName,
-    {
-        let mut result = self;
-        result.headers.append(key, value);
-
-        result
-    }
-
     pub(crate) async fn build(self) -> Result<HTTPClient, Error> {
         let mut cookie_provider = None;
         if self.cookie {
@@ -145,10 +179,12 @@ impl HTTPClientBuilder {
             .http2_keep_alive_interval(Duration::from_secs(5))
             .user_agent(self.user_agent);
 
-        if !is_ci::cached() {
-            client_builder = client_builder
-                .connect_timeout(Duration::from_secs(10))
-                .timeout(Duration::from_secs(30));
+        if let Some(connect_timeout) = self.connect_timeout {
+            client_builder = client_builder.connect_timeout(connect_timeout);
+        }
+
+        if let Some(timeout) = self.timeout {
+            client_builder = client_builder.timeout(timeout);
         }
 
         if let Some(jar) = &cookie_provider {
@@ -176,21 +212,32 @@ impl HTTPClientBuilder {
 
         Ok(HTTPClient {
             app_name: self.app_name,
+            profile: self.profile,
+            storage: self.storage,
+            key_source: self.key_source,
             cookie_provider,
             client: client_builder.build()?,
+            cassette: self.cassette,
+            cassette_rules: self.cassette_rules,
+            offline: self.offline,
+            retry_policy: self.retry_policy,
+            rate_limiter: self.rate_limit.map(RateLimiter::new),
         })
     }
 
     async fn create_cookie_provider(&self) -> Result<Jar, Error> {
-        let cookie_path = HTTPClientBuilder::cookie_path(self.app_name)?;
+        let cookie_path =
+            HTTPClientBuilder::cookie_path(self.app_name, &self.profile, &self.storage)?;
 
         let cookie_store = if fs::try_exists(&cookie_path).await? {
             info!("The cookie file is located at: `{}`", cookie_path.display());
 
             if let Ok(json) = super::aes_256_gcm_base64_decrypt(
                 &cookie_path,
-                HTTPClientBuilder::COOKIE_FILE_PASSWORD,
+                self.app_name,
+                &self.key_source,
                 HTTPClientBuilder::COOKIE_FILE_AAD,
+                HTTPClientBuilder::LEGACY_COOKIE_FILE_PASSWORD,
             ) {
                 CookieStore::load_json(json.as_bytes())?
             } else {
@@ -210,8 +257,8 @@ impl HTTPClientBuilder {
         Ok(Jar::new(cookie_store))
     }
 
-    fn cookie_path(app_name: &str) -> Result<PathBuf, Error> {
-        let mut config_path = crate::config_dir_path(app_name)?;
+    fn cookie_path(app_name: &str, profile: &Profile, storage: &Storage) -> Result<PathBuf, Error> {
+        let mut config_path = profile.config_dir(app_name, storage)?;
         config_path.push(HTTPClientBuilder::COOKIE_FILE_NAME);
 
         Ok(config_path)
@@ -219,10 +266,19 @@ impl HTTPClientBuilder {
 }
 
 #[must_use]
+#[derive(Clone)]
 pub(crate) struct HTTPClient {
     app_name: &'static str,
+    profile: Profile,
+    storage: Storage,
+    key_source: KeySource,
     cookie_provider: Option<Arc<Jar>>,
     client: Client,
+    cassette: Option<Arc<Cassette>>,
+    cassette_rules: CassetteRules,
+    offline: bool,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
 }
 
 impl HTTPClient {
@@ -230,6 +286,78 @@ impl HTTPClient {
         HTTPClientBuilder::new(app_name)
     }
 
+    /// Send the request and retry it according to the retry policy
+    pub(crate) async fn send(&self, request: RequestBuilder) -> Result<Response, Error> {
+        let policy = &self.retry_policy;
+        let mut attempt = 1;
+
+        loop {
+            // Requests with a streaming body can not be retried
+            let Some(current) = request.try_clone() else {
+                return self.send_once(request).await;
+            };
+            let last_attempt = attempt >= policy.max_attempts;
+
+            let delay = match self.send_once(current).await {
+                Ok(response) if !last_attempt && policy.retry_status(response.status()) => {
+                    let delay = policy
+                        .retry_after(response.headers())
+                        .unwrap_or_else(|| policy.backoff(attempt));
+                    if delay > policy.max_backoff {
+                        return Ok(response);
+                    }
+
+                    info!(
+                        "HTTP request failed, status code: `{}`, retry in {delay:?}, number of times: `{attempt}`",
+                        response.status()
+                    );
+                    delay
+                }
+                Err(err) if !last_attempt && policy.retry_error(&err) => {
+                    let delay = policy.backoff(attempt);
+
+                    info!(
+                        "HTTP request failed: `{err}`, retry in {delay:?}, number of times: `{attempt}`"
+                    );
+                    delay
+                }
+                result => return result,
+            };
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Send the request once, or answer it from the cassette if there is one
+    async fn send_once(&self, request: RequestBuilder) -> Result<Response, Error> {
+        if self.offline {
+            let request = request.build()?;
+            return Err(Error::NotCached(format!(
+                "the response of `{} {}`",
+                request.method(),
+                request.url()
+            )));
+        }
+
+        let replay = self
+            .cassette
+            .as_ref()
+            .is_some_and(|cassette| cassette.mode() == CassetteMode::Replay);
+        if let Some(rate_limiter) = self.rate_limiter.as_ref().filter(|_| !replay) {
+            rate_limiter.acquire().await;
+        }
+
+        match &self.cassette {
+            Some(cassette) => {
+                cassette
+                    .send(&self.client, &self.cassette_rules, request)
+                    .await
+            }
+            None => Ok(request.send().await?),
+        }
+    }
+
     pub(crate) fn add_cookie(&self, cookie_str: &str, url: &Url) -> Result<(), Error> {
         self.cookie_provider
             .as_ref()
@@ -255,13 +383,15 @@ impl HTTPClient {
             let result = simdutf8::basic::from_utf8(writer.buffer())?.to_string();
 
             if !result.is_empty() {
-                let cookie_path = HTTPClientBuilder::cookie_path(self.app_name)?;
+                let cookie_path =
+                    HTTPClientBuilder::cookie_path(self.app_name, &self.profile, &self.storage)?;
                 info!("Save the cookie file at: `{}`", cookie_path.display());
 
                 super::aes_256_gcm_base64_encrypt(
                     result,
                     cookie_path,
-                    HTTPClientBuilder::COOKIE_FILE_PASSWORD,
+                    self.app_name,
+                    &self.key_source,
                     HTTPClientBuilder::COOKIE_FILE_AAD,
                 )?;
             }
@@ -0,0 +1,51 @@
+use crate::{ContentInfo, ContentInfos, Error};
+
+/// Convert the index of a text paragraph, counted without the images, to the index in `ContentInfos`
+#[must_use]
+pub(crate) fn to_content_index(content_infos: &ContentInfos, text_index: usize) -> Option<usize> {
+    content_infos
+        .iter()
+        .enumerate()
+        .filter(|(_, content_info)| matches!(content_info, ContentInfo::Text(_)))
+        .nth(text_index)
+        .map(|(index, _)| index)
+}
+
+/// Convert the index in `ContentInfos` to the index of the text paragraph, counted without the
+/// images
+pub(crate) fn to_text_index(content_infos: &ContentInfos, index: usize) -> Result<usize, Error> {
+    match content_infos.get(index) {
+        Some(ContentInfo::Text(_)) => Ok(content_infos[..index]
+            .iter()
+            .filter(|content_info| matches!(content_info, ContentInfo::Text(_)))
+            .count()),
+        _ => Err(Error::NotFound(format!("text paragraph {index}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::Url;
+
+    use super::*;
+
+    #[test]
+    fn paragraph_index() -> Result<(), Error> {
+        let content_infos = vec![
+            ContentInfo::Text(String::from("第一段")),
+            ContentInfo::Image(Url::parse("https://example.com/1.png")?),
+            ContentInfo::Text(String::from("第二段")),
+        ];
+
+        assert_eq!(to_content_index(&content_infos, 0), Some(0));
+        assert_eq!(to_content_index(&content_infos, 1), Some(2));
+        assert_eq!(to_content_index(&content_infos, 2), None);
+
+        assert_eq!(to_text_index(&content_infos, 0)?, 0);
+        assert!(to_text_index(&content_infos, 1).is_err());
+        assert_eq!(to_text_index(&content_infos, 2)?, 1);
+        assert!(to_text_index(&content_infos, 3).is_err());
+
+        Ok(())
+    }
+}
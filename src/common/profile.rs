@@ -0,0 +1,170 @@
+use std::{fs, path::PathBuf};
+
+use tracing::info;
+
+use crate::{Error, Source, Storage};
+
+const PROFILES_DIR_NAME: &str = "profiles";
+
+/// Whether a profile uses the database of the source or its own one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DatabaseSharing {
+    /// Share the novels, chapters and images with the other profiles, the user information,
+    /// the bookshelf and whether a chapter has been bought are still kept per profile
+    #[default]
+    Shared,
+    /// Use a database that only belongs to this profile
+    Separate,
+}
+
+/// A named account of a source, each profile has its own config file and cookies
+#[must_use]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    name: String,
+    database: DatabaseSharing,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: String::from(Profile::DEFAULT_NAME),
+            database: DatabaseSharing::default(),
+        }
+    }
+}
+
+impl Profile {
+    /// Name of the profile used when none is given, its files live in the top-level directories
+    pub const DEFAULT_NAME: &'static str = "default";
+
+    /// Create a profile, the name may only contain ASCII letters, digits, `-` and `_`
+    pub fn new<T>(name: T) -> Result<Self, Error>
+    where
+        T: AsRef<str>,
+    {
+        let name = name.as_ref();
+
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(Error::InvalidOptions(format!(
+                "Invalid profile name: `{name}`"
+            )));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            ..Default::default()
+        })
+    }
+
+    /// Set whether the profile shares the database with the other profiles
+    pub fn database(self, database: DatabaseSharing) -> Self {
+        Self { database, ..self }
+    }
+
+    /// Get the name of the profile
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether this is the default profile
+    #[must_use]
+    pub fn is_default(&self) -> bool {
+        self.name == Profile::DEFAULT_NAME
+    }
+
+    pub(crate) fn config_dir(&self, app_name: &str, storage: &Storage) -> Result<PathBuf, Error> {
+        let mut path = storage.config_path(app_name)?;
+        if !self.is_default() {
+            path.push(PROFILES_DIR_NAME);
+            path.push(&self.name);
+        }
+
+        Ok(path)
+    }
+
+    pub(crate) fn data_dir(&self, app_name: &str, storage: &Storage) -> Result<PathBuf, Error> {
+        let mut path = storage.data_path(app_name)?;
+        if !self.is_default() && self.database == DatabaseSharing::Separate {
+            path.push(PROFILES_DIR_NAME);
+            path.push(&self.name);
+        }
+
+        Ok(path)
+    }
+
+    /// Prefix of the cached per-account data in a shared database
+    pub(crate) fn cache_namespace(&self) -> Option<&str> {
+        if self.is_default() {
+            None
+        } else {
+            Some(&self.name)
+        }
+    }
+}
+
+/// List the profiles of the source, the default profile is always included
+pub fn profiles(source: Source, storage: &Storage) -> Result<Vec<String>, Error> {
+    let mut result = vec![String::from(Profile::DEFAULT_NAME)];
+
+    let profiles_dir = storage
+        .config_path(source.as_str())?
+        .join(PROFILES_DIR_NAME);
+    if profiles_dir.try_exists()? {
+        let mut names = Vec::new();
+
+        for entry in fs::read_dir(profiles_dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                names.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+
+        names.sort_unstable();
+        result.extend(names);
+    }
+
+    Ok(result)
+}
+
+/// Delete the config file, the cookies and the separate database of a profile
+pub fn delete_profile(source: Source, name: &str, storage: &Storage) -> Result<(), Error> {
+    let app_name = source.as_str();
+    let profile = Profile::new(name)?;
+
+    if profile.is_default() {
+        let config_dir = profile.config_dir(app_name, storage)?;
+
+        for file_name in [
+            crate::CONFIG_FILE_NAME,
+            crate::HTTPClientBuilder::COOKIE_FILE_NAME,
+        ] {
+            let path = config_dir.join(file_name);
+            if path.try_exists()? {
+                fs::remove_file(path)?;
+            }
+        }
+    } else {
+        let config_dir = profile.config_dir(app_name, storage)?;
+        if !config_dir.try_exists()? {
+            return Err(Error::NotFound(format!("The profile `{name}`")));
+        }
+        fs::remove_dir_all(config_dir)?;
+
+        let data_dir = profile
+            .database(DatabaseSharing::Separate)
+            .data_dir(app_name, storage)?;
+        if data_dir.try_exists()? {
+            fs::remove_dir_all(data_dir)?;
+        }
+    }
+
+    info!("The profile `{name}` of `{app_name}` has been deleted");
+
+    Ok(())
+}
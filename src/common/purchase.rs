@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use crate::{ChapterInfo, ChapterPurchase, Error, PurchaseStatus, VolumeInfos};
+
+/// The chapters that need to be bought, free, purchased and invalid chapters are skipped
+#[must_use]
+pub(crate) fn chapters_to_buy(chapter_infos: &[ChapterInfo]) -> Vec<&ChapterInfo> {
+    chapter_infos
+        .iter()
+        .filter(|chapter_info| chapter_info.payment_required() && chapter_info.is_valid())
+        .collect()
+}
+
+/// The total price of the chapters, chapters without a price are counted as free
+#[must_use]
+pub(crate) fn total_price(chapter_infos: &[&ChapterInfo]) -> u32 {
+    chapter_infos
+        .iter()
+        .map(|chapter_info| chapter_info.price.unwrap_or_default() as u32)
+        .sum()
+}
+
+/// Make sure the balance can pay for all the chapters before anything is bought
+pub(crate) fn check_balance(chapter_infos: &[&ChapterInfo], money: u32) -> Result<(), Error> {
+    let total = total_price(chapter_infos);
+
+    if total > money {
+        Err(Error::InsufficientBalance(format!(
+            "{total} is required, but the balance is {money}"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// All chapters of the novel, in reading order
+#[must_use]
+pub(crate) fn all_chapters(volume_infos: VolumeInfos) -> Vec<ChapterInfo> {
+    volume_infos
+        .into_iter()
+        .flat_map(|volume_info| volume_info.chapter_infos)
+        .collect()
+}
+
+/// Ids of the chapters
+#[must_use]
+pub(crate) fn chapter_ids(chapter_infos: &[&ChapterInfo]) -> Vec<u32> {
+    chapter_infos
+        .iter()
+        .map(|chapter_info| chapter_info.id)
+        .collect()
+}
+
+/// Record the result of an order that contains the chapters
+pub(crate) fn record_order(
+    statuses: &mut HashMap<u32, PurchaseStatus>,
+    chapter_ids: &[u32],
+    result: Result<(), Error>,
+) {
+    let status = match result {
+        Ok(()) => PurchaseStatus::Bought,
+        Err(error) => PurchaseStatus::Failed(error.to_string()),
+    };
+
+    for chapter_id in chapter_ids {
+        statuses.insert(*chapter_id, status.clone());
+    }
+}
+
+/// The result of every chapter in the dry-run mode, nothing is bought
+#[must_use]
+pub(crate) fn dry_run_results(
+    infos: &[ChapterInfo],
+    chapter_infos: &[&ChapterInfo],
+) -> Vec<ChapterPurchase> {
+    let statuses = chapter_infos
+        .iter()
+        .map(|chapter_info| (chapter_info.id, PurchaseStatus::DryRun))
+        .collect();
+
+    purchase_results(infos, statuses)
+}
+
+/// The result of every chapter in the same order, chapters without an order are skipped
+#[must_use]
+pub(crate) fn purchase_results(
+    chapter_infos: &[ChapterInfo],
+    mut statuses: HashMap<u32, PurchaseStatus>,
+) -> Vec<ChapterPurchase> {
+    chapter_infos
+        .iter()
+        .map(|chapter_info| ChapterPurchase {
+            chapter_id: chapter_info.id,
+            price: chapter_info.price,
+            status: statuses
+                .remove(&chapter_info.id)
+                .unwrap_or(PurchaseStatus::Skipped),
+        })
+        .collect()
+}
@@ -0,0 +1,84 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Maximum request rate to a host, `requests` per `per` with bursts of up to `burst` requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Number of requests allowed in each period
+    pub requests: u32,
+    /// Length of the period
+    pub per: Duration,
+    /// Number of requests that can be sent at once after the client has been idle
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// Allow `requests` requests per second, without bursts
+    pub fn per_second(requests: u32) -> Self {
+        Self {
+            requests,
+            per: Duration::from_secs(1),
+            burst: 1,
+        }
+    }
+
+    /// Wait at least `interval` between two requests
+    pub fn min_interval(interval: Duration) -> Self {
+        Self {
+            requests: 1,
+            per: interval,
+            burst: 1,
+        }
+    }
+
+    /// Set the number of requests that can be sent at once
+    pub fn burst(self, burst: u32) -> Self {
+        Self { burst, ..self }
+    }
+
+    fn interval(&self) -> Duration {
+        self.per / self.requests.max(1)
+    }
+}
+
+/// Token bucket shared by all clones of an `HTTPClient`
+#[derive(Clone)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    /// The time at which the bucket would be full again
+    next: Arc<Mutex<Instant>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            next: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Wait until a request can be sent
+    pub(crate) async fn acquire(&self) {
+        let delay = {
+            let mut next = self.next.lock().unwrap();
+            let now = Instant::now();
+
+            let interval = self.limit.interval();
+            let tolerance = interval * self.limit.burst.saturating_sub(1);
+
+            let start = (*next).max(now);
+            *next = start + interval;
+
+            start
+                .checked_sub(tolerance)
+                .unwrap_or(now)
+                .saturating_duration_since(now)
+        };
+
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use http::{header::RETRY_AFTER, HeaderMap, StatusCode};
+use rand::Rng;
+
+use crate::Error;
+
+/// When and how failed HTTP requests are retried
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts including the first one, `1` disables retrying
+    pub max_attempts: u32,
+    /// Delay before the first retry, it doubles after each attempt
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between two attempts
+    pub max_backoff: Duration,
+    /// Randomize the delay between half and all of the backoff
+    pub jitter: bool,
+    /// Retry when the connection can not be established
+    pub retry_connect_errors: bool,
+    /// Retry when the request times out
+    pub retry_timeouts: bool,
+    /// Status codes of the responses that are retried
+    pub retry_status_codes: Vec<StatusCode>,
+    /// Wait for the delay given by the `Retry-After` header, the response is returned
+    /// as is when it is longer than `max_backoff`
+    pub respect_retry_after: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            retry_connect_errors: true,
+            retry_timeouts: true,
+            retry_status_codes: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            respect_retry_after: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn retry_error(&self, err: &Error) -> bool {
+        match err {
+            Error::Reqwest(err) => {
+                (self.retry_connect_errors && err.is_connect())
+                    || (self.retry_timeouts && err.is_timeout())
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn retry_status(&self, status: StatusCode) -> bool {
+        self.retry_status_codes.contains(&status)
+    }
+
+    /// The delay before the next attempt, `attempt` starts from 1
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+
+        if self.jitter && !backoff.is_zero() {
+            rand::thread_rng().gen_range(backoff / 2..=backoff)
+        } else {
+            backoff
+        }
+    }
+
+    /// The delay requested by the server, `None` if it is missing or invalid
+    pub(crate) fn retry_after(&self, headers: &HeaderMap) -> Option<Duration> {
+        if !self.respect_retry_after {
+            return None;
+        }
+
+        let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+        match value.parse::<u64>() {
+            Ok(seconds) => Some(Duration::from_secs(seconds)),
+            Err(_) => {
+                let date = DateTime::parse_from_rfc2822(value)
+                    .ok()?
+                    .with_timezone(&Utc);
+                Some((date - Utc::now()).to_std().unwrap_or_default())
+            }
+        }
+    }
+}
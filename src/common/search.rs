@@ -0,0 +1,56 @@
+use std::collections::VecDeque;
+
+use futures_util::{stream, Stream};
+
+use crate::{Client, Error, NovelSummary, Options};
+
+struct SearchState {
+    page: Option<u16>,
+    buffer: VecDeque<NovelSummary>,
+    count: usize,
+}
+
+/// Search the novels matching `option` page by page
+///
+/// The stream ends after the last page, after an error or once `limit` novels have been returned
+pub fn search_novels<'a, C>(
+    client: &'a C,
+    option: &'a Options,
+    limit: Option<usize>,
+) -> impl Stream<Item = Result<NovelSummary, Error>> + Send + 'a
+where
+    C: Client + Sync,
+{
+    let state = SearchState {
+        page: Some(0),
+        buffer: VecDeque::new(),
+        count: 0,
+    };
+
+    stream::unfold(state, move |mut state| async move {
+        loop {
+            if limit.is_some_and(|limit| state.count >= limit) {
+                return None;
+            }
+
+            if let Some(summary) = state.buffer.pop_front() {
+                state.count += 1;
+                return Some((Ok(summary), state));
+            }
+
+            let page = state.page?;
+            match client.search_summaries(option, page).await {
+                // The page may be empty if all its novels are filtered out locally
+                Ok(Some(summaries)) => {
+                    state.page = page.checked_add(1);
+                    state.buffer.extend(summaries);
+                }
+                Ok(None) => return None,
+                Err(error) => {
+                    state.page = None;
+                    return Some((Err(error), state));
+                }
+            }
+        }
+    })
+}
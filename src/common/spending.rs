@@ -0,0 +1,191 @@
+use std::{collections::HashMap, sync::Arc};
+
+use itertools::Itertools;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::{ChapterInfo, Error, NovelDB};
+
+/// Limits on the currency spent by a client
+///
+/// The budgets are compared with `ChapterInfo::price` before anything is bought
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpendingPolicy {
+    /// Maximum amount spent by the client, `None` means unlimited
+    pub session_budget: Option<u32>,
+    /// Maximum amount spent on a novel, counted from the purchases saved in the audit log
+    pub novel_budget: Option<u32>,
+    /// Only report what would be bought, nothing is spent
+    pub dry_run: bool,
+}
+
+impl SpendingPolicy {
+    /// Only report what would be bought, without budgets
+    pub fn dry_run() -> Self {
+        Self {
+            dry_run: true,
+            ..Default::default()
+        }
+    }
+
+    /// Set the maximum amount spent by the client
+    pub fn session_budget(self, budget: u32) -> Self {
+        Self {
+            session_budget: Some(budget),
+            ..self
+        }
+    }
+
+    /// Set the maximum amount spent on a novel
+    pub fn novel_budget(self, budget: u32) -> Self {
+        Self {
+            novel_budget: Some(budget),
+            ..self
+        }
+    }
+}
+
+/// Spending policy and the amount spent, shared by all clones of the client options
+#[derive(Clone, Default)]
+pub(crate) struct SpendingGuard {
+    policy: SpendingPolicy,
+    spent: Arc<Mutex<Spent>>,
+}
+
+#[derive(Default)]
+struct Spent {
+    /// Spent or reserved in the session
+    session: u32,
+    /// Reserved for each novel and not yet saved to the audit log
+    novels: HashMap<u32, u32>,
+}
+
+impl Spent {
+    fn reserve(&mut self, chapter_infos: &[&ChapterInfo]) {
+        self.session += crate::total_price(chapter_infos);
+
+        for chapter_info in chapter_infos {
+            if let Some(novel_id) = chapter_info.novel_id {
+                *self.novels.entry(novel_id).or_default() +=
+                    chapter_info.price.unwrap_or_default() as u32;
+            }
+        }
+    }
+
+    fn release_novels(&mut self, chapter_infos: &[&ChapterInfo]) {
+        for chapter_info in chapter_infos {
+            if let Some(novel_id) = chapter_info.novel_id {
+                if let Some(reserved) = self.novels.get_mut(&novel_id) {
+                    *reserved =
+                        reserved.saturating_sub(chapter_info.price.unwrap_or_default() as u32);
+                }
+            }
+        }
+    }
+}
+
+impl SpendingGuard {
+    pub(crate) fn new(policy: SpendingPolicy) -> Self {
+        Self {
+            policy,
+            spent: Arc::new(Mutex::new(Spent::default())),
+        }
+    }
+
+    /// Make sure buying the chapters does not exceed any budget and reserve their price,
+    /// the reservation must be followed by `record` or `release`
+    pub(crate) async fn check(
+        &self,
+        db: &NovelDB,
+        chapter_infos: &[&ChapterInfo],
+    ) -> Result<(), Error> {
+        // Held until the price is reserved, so that concurrent purchases are checked one by one
+        let mut spent = self.spent.lock().await;
+
+        if let Some(budget) = self.policy.session_budget {
+            let required = crate::total_price(chapter_infos);
+
+            if spent.session + required > budget {
+                return Err(Error::BudgetExceeded(format!(
+                    "session budget {budget}, spent {}, required {required}",
+                    spent.session
+                )));
+            }
+        }
+
+        if let Some(budget) = self.policy.novel_budget {
+            let novel_ids = chapter_infos
+                .iter()
+                .filter_map(|chapter_info| chapter_info.novel_id)
+                .unique()
+                .collect::<Vec<_>>();
+
+            for novel_id in novel_ids {
+                let novel_spent = db.find_novel_spending(novel_id).await?
+                    + spent.novels.get(&novel_id).copied().unwrap_or_default();
+                let required = crate::total_price(
+                    &chapter_infos
+                        .iter()
+                        .filter(|chapter_info| chapter_info.novel_id == Some(novel_id))
+                        .copied()
+                        .collect::<Vec<_>>(),
+                );
+
+                if novel_spent + required > budget {
+                    return Err(Error::BudgetExceeded(format!(
+                        "novel {novel_id} budget {budget}, spent {novel_spent}, required {required}"
+                    )));
+                }
+            }
+        }
+
+        if !self.policy.dry_run {
+            spent.reserve(chapter_infos);
+        }
+
+        Ok(())
+    }
+
+    /// Whether nothing should be bought, the chapters that would be bought are logged
+    pub(crate) fn dry_run(&self, chapter_infos: &[&ChapterInfo]) -> bool {
+        if self.policy.dry_run {
+            info!(
+                "Dry run, {} chapters would be bought for {}: {:?}",
+                chapter_infos.len(),
+                crate::total_price(chapter_infos),
+                chapter_infos
+                    .iter()
+                    .map(|chapter_info| chapter_info.id)
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        self.policy.dry_run
+    }
+
+    /// Save the bought chapters to the audit log, their price stays spent in the session
+    pub(crate) async fn record(
+        &self,
+        db: &NovelDB,
+        chapter_infos: &[&ChapterInfo],
+    ) -> Result<(), Error> {
+        let mut spent = self.spent.lock().await;
+        let result = db.insert_purchases(chapter_infos).await;
+        spent.release_novels(chapter_infos);
+
+        result
+    }
+
+    /// Release the price reserved for chapters that were not bought
+    pub(crate) async fn release(&self, chapter_infos: &[&ChapterInfo]) {
+        if self.policy.dry_run {
+            return;
+        }
+
+        let mut spent = self.spent.lock().await;
+        spent.session = spent
+            .session
+            .saturating_sub(crate::total_price(chapter_infos));
+        spent.release_novels(chapter_infos);
+    }
+}
@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use crate::Error;
+
+/// Where the config files, the cookies and the database of the sources are stored
+///
+/// Each source uses a subdirectory named after it, e.g. `<config_dir>/sfacg`,
+/// the directories that are not set fall back to `config_dir_path` and `data_dir_path`
+#[must_use]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Storage {
+    config_dir: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+}
+
+impl Storage {
+    /// Store everything below `root`, in its `config` and `data` subdirectories
+    pub fn root<T>(root: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        let root = root.into();
+
+        Self {
+            config_dir: Some(root.join("config")),
+            data_dir: Some(root.join("data")),
+        }
+    }
+
+    /// Set the directory of the config files and the cookies
+    pub fn config_dir<T>(self, config_dir: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        Self {
+            config_dir: Some(config_dir.into()),
+            ..self
+        }
+    }
+
+    /// Set the directory of the database
+    pub fn data_dir<T>(self, data_dir: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        Self {
+            data_dir: Some(data_dir.into()),
+            ..self
+        }
+    }
+
+    pub(crate) fn config_path(&self, app_name: &str) -> Result<PathBuf, Error> {
+        match &self.config_dir {
+            Some(config_dir) => Ok(config_dir.join(app_name)),
+            None => crate::config_dir_path(app_name),
+        }
+    }
+
+    pub(crate) fn data_path(&self, app_name: &str) -> Result<PathBuf, Error> {
+        match &self.data_dir {
+            Some(data_dir) => Ok(data_dir.join(app_name)),
+            None => crate::data_dir_path(app_name),
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use std::{env, path::PathBuf};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
 use directories::{ProjectDirs, UserDirs};
 use tracing::error;
@@ -16,28 +19,60 @@ pub fn home_dir_path() -> Result<PathBuf, Error> {
     }
 }
 
+/// Environment variable that overrides the root of the config directories
+pub const CONFIG_DIR_ENV: &str = "NOVEL_API_CONFIG_DIR";
+/// Environment variable that overrides the root of the local data directories
+pub const DATA_DIR_ENV: &str = "NOVEL_API_DATA_DIR";
+
 /// Return the path to the project's config directory or the current directory on failure
+///
+/// If `NOVEL_API_CONFIG_DIR` is set, `$NOVEL_API_CONFIG_DIR/<app_name>` is returned
 pub fn config_dir_path(app_name: &str) -> Result<PathBuf, Error> {
-    match ProjectDirs::from("", "novel-rs", app_name) {
-        Some(dir) => Ok(dir.config_dir().to_path_buf()),
-        None => {
-            error!("Failed to get the path to the project's config directory, using the current working directory");
-            Ok(env::current_dir()?)
-        }
-    }
+    dir_path(
+        env_dir(CONFIG_DIR_ENV),
+        app_name,
+        ProjectDirs::config_dir,
+        "config",
+    )
 }
 
 /// Return the path to the project's local data directory or the current directory on failure
+///
+/// If `NOVEL_API_DATA_DIR` is set, `$NOVEL_API_DATA_DIR/<app_name>` is returned
 pub fn data_dir_path(app_name: &str) -> Result<PathBuf, Error> {
+    dir_path(
+        env_dir(DATA_DIR_ENV),
+        app_name,
+        ProjectDirs::data_local_dir,
+        "local data",
+    )
+}
+
+fn dir_path(
+    root: Option<PathBuf>,
+    app_name: &str,
+    project_dir: fn(&ProjectDirs) -> &Path,
+    kind: &str,
+) -> Result<PathBuf, Error> {
+    if let Some(root) = root {
+        return Ok(root.join(app_name));
+    }
+
     match ProjectDirs::from("", "novel-rs", app_name) {
-        Some(dir) => Ok(dir.data_local_dir().to_path_buf()),
+        Some(dir) => Ok(project_dir(&dir).to_path_buf()),
         None => {
-            error!("Failed to get the path to the project's local data directory, using the current working directory");
+            error!("Failed to get the path to the project's {kind} directory, using the current working directory");
             Ok(env::current_dir()?)
         }
     }
 }
 
+fn env_dir(key: &str) -> Option<PathBuf> {
+    env::var_os(key)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,4 +88,19 @@ mod tests {
         let _ = super::data_dir_path("test-app")?;
         Ok(())
     }
+
+    #[test]
+    fn root_override() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+
+        let data_dir = dir_path(
+            Some(dir.path().to_path_buf()),
+            "test-app-env",
+            ProjectDirs::data_local_dir,
+            "local data",
+        )?;
+        assert_eq!(data_dir, dir.path().join("test-app-env"));
+
+        Ok(())
+    }
 }
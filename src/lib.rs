@@ -1,10 +1,12 @@
 //! Novel APIs from various sources
 
+mod any;
 mod ciweimao;
 mod ciyuanji;
 mod common;
 mod sfacg;
 
+pub use any::*;
 pub use ciweimao::*;
 pub use ciyuanji::*;
 pub use common::*;
@@ -1,7 +1,7 @@
 mod structure;
 mod utils;
 
-use std::{io::Cursor, path::PathBuf};
+use std::{collections::HashMap, io::Cursor};
 
 use chrono::{DateTime, Utc};
 use chrono_tz::{Asia::Shanghai, Tz};
@@ -12,17 +12,17 @@ use url::Url;
 
 use self::structure::*;
 use crate::{
-    Category, ChapterInfo, Client, ContentInfo, ContentInfos, Error, FindImageResult,
-    FindTextResult, HTTPClient, NovelDB, NovelInfo, Options, Tag, UserInfo, VolumeInfo,
-    VolumeInfos, WordCountRange,
+    Author, Bookshelf, CacheKey, Category, ChapterInfo, ChapterPurchase, Client, ClientOptions,
+    Comment, ContentInfo, ContentInfos, Error, FindImageResult, FindTextResult, Freshness,
+    HTTPClient, NovelDB, NovelInfo, NovelSummary, Options, PurchaseRecord, RankingKind,
+    RankingPeriod, ReadingProgress, Review, SortOrder, Tag, UserInfo, VolumeInfo, VolumeInfos,
+    WordCountRange,
 };
 
 /// Sfacg client, use it to access Apis
 #[must_use]
 pub struct SfacgClient {
-    proxy: Option<Url>,
-    no_proxy: bool,
-    cert_path: Option<PathBuf>,
+    options: ClientOptions,
 
     client: OnceCell<HTTPClient>,
     client_rss: OnceCell<HTTPClient>,
@@ -31,18 +31,6 @@ pub struct SfacgClient {
 }
 
 impl Client for SfacgClient {
-    fn proxy(&mut self, proxy: Url) {
-        self.proxy = Some(proxy);
-    }
-
-    fn no_proxy(&mut self) {
-        self.no_proxy = true;
-    }
-
-    fn cert(&mut self, cert_path: PathBuf) {
-        self.cert_path = Some(cert_path);
-    }
-
     async fn shutdown(&self) -> Result<(), Error> {
         self.client().await?.shutdown()
     }
@@ -79,14 +67,25 @@ impl Client for SfacgClient {
     }
 
     async fn user_info(&self) -> Result<UserInfo, Error> {
+        if self.options.offline {
+            return self.db().await?.find_cache(CacheKey::UserInfo).await;
+        }
+
         let response = self.get("/user").await?.json::<UserInfoResponse>().await?;
         response.status.check()?;
         let data = response.data.unwrap();
 
-        Ok(UserInfo {
+        let user_info = UserInfo {
             nickname: data.nick_name.trim().to_string(),
             avatar: Some(data.avatar),
-        })
+        };
+
+        self.db()
+            .await?
+            .save_cache(CacheKey::UserInfo, &user_info)
+            .await?;
+
+        Ok(user_info)
     }
 
     async fn money(&self) -> Result<u32, Error> {
@@ -124,6 +123,19 @@ impl Client for SfacgClient {
     }
 
     async fn bookshelf_infos(&self) -> Result<Vec<u32>, Error> {
+        Ok(self
+            .bookshelves()
+            .await?
+            .into_iter()
+            .flat_map(|bookshelf| bookshelf.novel_ids)
+            .collect())
+    }
+
+    async fn bookshelves(&self) -> Result<Vec<Bookshelf>, Error> {
+        if self.options.offline {
+            return self.db().await?.find_cache(CacheKey::Bookshelves).await;
+        }
+
         let response = self
             .get_query("/user/Pockets", BookshelfInfoRequest { expand: "novels" })
             .await?
@@ -132,25 +144,79 @@ impl Client for SfacgClient {
         response.status.check()?;
         let data = response.data.unwrap();
 
-        let mut result = Vec::with_capacity(32);
+        let mut result = Vec::with_capacity(4);
         for info in data {
-            if info.expand.is_some() {
-                let novels = info.expand.unwrap().novels;
+            let mut novel_ids = Vec::with_capacity(32);
 
-                if novels.is_some() {
-                    for novel_info in novels.unwrap() {
-                        result.push(novel_info.novel_id);
-                    }
+            if let Some(novels) = info.expand.and_then(|expand| expand.novels) {
+                for novel_info in novels {
+                    novel_ids.push(novel_info.novel_id);
                 }
             }
+
+            result.push(Bookshelf {
+                id: info.pocket_id,
+                name: info.name.trim().to_string(),
+                novel_ids,
+            });
         }
 
+        self.db()
+            .await?
+            .save_cache(CacheKey::Bookshelves, &result)
+            .await?;
+
         Ok(result)
     }
 
+    async fn add_to_bookshelf(&self, novel_id: u32, shelf_id: u32) -> Result<(), Error> {
+        let response = self
+            .post(
+                format!("/pockets/{shelf_id}/novels"),
+                AddToBookshelfRequest { novel_id },
+            )
+            .await?
+            .json::<GenericResponse>()
+            .await?;
+        response.status.check()?;
+
+        Ok(())
+    }
+
+    async fn remove_from_bookshelf(&self, novel_id: u32, shelf_id: u32) -> Result<(), Error> {
+        let response = self
+            .delete(format!("/pockets/{shelf_id}/novels/{novel_id}"))
+            .await?
+            .json::<GenericResponse>()
+            .await?;
+        response.status.check()?;
+
+        Ok(())
+    }
+
+    async fn move_between_shelves(
+        &self,
+        novel_id: u32,
+        from_shelf_id: u32,
+        to_shelf_id: u32,
+    ) -> Result<(), Error> {
+        // There is no API to move a novel, add it first so that it is never lost
+        self.add_to_bookshelf(novel_id, to_shelf_id).await?;
+        self.remove_from_bookshelf(novel_id, from_shelf_id).await
+    }
+
     async fn novel_info(&self, id: u32) -> Result<Option<NovelInfo>, Error> {
         assert!(id > 0 && id <= i32::MAX as u32);
 
+        if let Some(novel_info) = self
+            .db()
+            .await?
+            .find_novel_info(id, self.metadata_freshness())
+            .await?
+        {
+            return Ok(Some(novel_info));
+        }
+
         let response = self
             .get_query(
                 format!("/novels/{id}"),
@@ -176,6 +242,7 @@ impl Client for SfacgClient {
         let novel_info = NovelInfo {
             id,
             name: data.novel_name.trim().to_string(),
+            author_id: Some(data.author_id),
             author_name: data.author_name.trim().to_string(),
             cover_url: Some(data.novel_cover),
             introduction: SfacgClient::parse_intro(data.expand.intro),
@@ -188,12 +255,67 @@ impl Client for SfacgClient {
             tags: self.parse_tags(data.expand.sys_tags).await?,
         };
 
+        self.db().await?.save_novel_info(&novel_info).await?;
+
         Ok(Some(novel_info))
     }
 
+    async fn author_info(&self, id: u32) -> Result<Option<Author>, Error> {
+        assert!(id <= i32::MAX as u32);
+
+        let response = self
+            .get(format!("/authors/{id}"))
+            .await?
+            .json::<AuthorResponse>()
+            .await?;
+        if response.status.not_found() {
+            return Ok(None);
+        }
+        response.status.check()?;
+        let data = response.data.unwrap();
+
+        Ok(Some(Author {
+            id: data.author_id,
+            name: data.author_name.trim().to_string(),
+            avatar: data.avatar,
+            introduction: data.intro.and_then(SfacgClient::parse_intro),
+            follower_count: Some(data.fans_num),
+        }))
+    }
+
+    async fn author_novels(&self, id: u32) -> Result<Vec<NovelSummary>, Error> {
+        assert!(id <= i32::MAX as u32);
+
+        let response = self
+            .get_query(
+                format!("/authors/{id}/novels"),
+                AuthorNovelsRequest { expand: "sysTags" },
+            )
+            .await?
+            .json::<NovelsResponse>()
+            .await?;
+        response.status.check()?;
+
+        let mut result = Vec::new();
+        for novel_data in response.data.unwrap() {
+            result.push(self.parse_summary(novel_data).await?);
+        }
+
+        Ok(result)
+    }
+
     async fn volume_infos(&self, id: u32) -> Result<Option<VolumeInfos>, Error> {
         assert!(id <= i32::MAX as u32);
 
+        if let Some(volume_infos) = self
+            .db()
+            .await?
+            .find_volume_infos(id, self.metadata_freshness())
+            .await?
+        {
+            return Ok(Some(volume_infos));
+        }
+
         let response = self
             .get(format!("/novels/{id}/dirs"))
             .await?
@@ -234,9 +356,25 @@ impl Client for SfacgClient {
             volumes.push(volume_info);
         }
 
+        self.db().await?.save_volume_infos(id, &volumes).await?;
+
         Ok(Some(volumes))
     }
 
+    async fn cached_novel_info(&self, id: u32) -> Result<Option<NovelInfo>, Error> {
+        self.db()
+            .await?
+            .find_novel_info(id, Freshness::Forever)
+            .await
+    }
+
+    async fn cached_volume_infos(&self, id: u32) -> Result<Option<VolumeInfos>, Error> {
+        self.db()
+            .await?
+            .find_volume_infos(id, Freshness::Forever)
+            .await
+    }
+
     async fn content_infos(&self, info: &ChapterInfo) -> Result<ContentInfos, Error> {
         let content;
 
@@ -244,6 +382,9 @@ impl Client for SfacgClient {
             FindTextResult::Ok(str) => {
                 content = str;
             }
+            FindTextResult::Outdate if self.options.offline => {
+                content = self.db().await?.find_saved_text(info).await?;
+            }
             other => {
                 let response = self
                     .get_query(
@@ -291,15 +432,165 @@ impl Client for SfacgClient {
         Ok(content_infos)
     }
 
-    async fn buy_chapter(&self, info: &ChapterInfo) -> Result<(), Error> {
+    async fn chapter_comments(
+        &self,
+        info: &ChapterInfo,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error> {
         let response = self
-            .post(
-                &format!("/novels/{}/orderedchaps", info.novel_id.unwrap()),
-                BuyChapterRequest {
-                    order_all: false,
-                    auto_order: false,
-                    chap_ids: vec![info.id],
-                    order_type: "readOrder",
+            .get_query(
+                format!("/Chaps/{}/Cmts", info.id),
+                CommentsRequest {
+                    page,
+                    size: SfacgClient::MAX_PAGE_SIZE,
+                    sort: "timeline",
+                },
+            )
+            .await?
+            .json::<CommentsResponse>()
+            .await?;
+        response.status.check()?;
+
+        Ok(SfacgClient::parse_comments(response.data.unwrap()))
+    }
+
+    async fn paragraph_comment_counts(
+        &self,
+        info: &ChapterInfo,
+    ) -> Result<HashMap<usize, u32>, Error> {
+        let response = self
+            .get(format!("/Chaps/{}/paragraphCmts/count", info.id))
+            .await?
+            .json::<ParagraphCommentCountsResponse>()
+            .await?;
+        response.status.check()?;
+
+        // The paragraph index of the server does not count the images
+        let content_infos = self.content_infos(info).await?;
+
+        let mut result = HashMap::new();
+        for item in response.data.unwrap() {
+            match crate::to_content_index(&content_infos, item.paragraph_index) {
+                Some(index) => {
+                    result.insert(index, item.count);
+                }
+                None => error!("The paragraph does not exist: {}", item.paragraph_index),
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn paragraph_comments(
+        &self,
+        info: &ChapterInfo,
+        index: usize,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error> {
+        let content_infos = self.content_infos(info).await?;
+
+        let response = self
+            .get_query(
+                format!("/Chaps/{}/paragraphCmts", info.id),
+                ParagraphCommentsRequest {
+                    paragraph_index: crate::to_text_index(&content_infos, index)?,
+                    page,
+                    size: SfacgClient::MAX_PAGE_SIZE,
+                    sort: "timeline",
+                },
+            )
+            .await?
+            .json::<CommentsResponse>()
+            .await?;
+        response.status.check()?;
+
+        Ok(SfacgClient::parse_comments(response.data.unwrap()))
+    }
+
+    async fn novel_reviews(&self, novel_id: u32, page: u16) -> Result<Option<Vec<Review>>, Error> {
+        let response = self
+            .get_query(
+                format!("/novels/{novel_id}/Cmts"),
+                CommentsRequest {
+                    page,
+                    size: SfacgClient::MAX_PAGE_SIZE,
+                    sort: "timeline",
+                },
+            )
+            .await?
+            .json::<ReviewsResponse>()
+            .await?;
+        response.status.check()?;
+        let data = response.data.unwrap();
+
+        if data.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            data.into_iter()
+                .map(|review| Review {
+                    id: review.cmt_id,
+                    title: review
+                        .title
+                        .map(|title| title.trim().to_string())
+                        .filter(|title| !title.is_empty()),
+                    content: review.content.trim().to_string(),
+                    author_name: review.user_name.trim().to_string(),
+                    author_avatar: review.avatar,
+                    create_time: Some(review.create_time),
+                    like_count: Some(review.praise_num),
+                    reply_count: Some(review.reply_num),
+                })
+                .collect(),
+        ))
+    }
+
+    async fn review_replies(
+        &self,
+        review_id: u32,
+        page: u16,
+    ) -> Result<Option<Vec<Comment>>, Error> {
+        let response = self
+            .get_query(
+                format!("/cmts/{review_id}/replys"),
+                CommentsRequest {
+                    page,
+                    size: SfacgClient::MAX_PAGE_SIZE,
+                    sort: "timeline",
+                },
+            )
+            .await?
+            .json::<CommentsResponse>()
+            .await?;
+        response.status.check()?;
+
+        Ok(SfacgClient::parse_comments(response.data.unwrap()))
+    }
+
+    async fn reading_progress(&self, novel_id: u32) -> Result<Option<ReadingProgress>, Error> {
+        let response = self
+            .get(format!("/user/readrecords/{novel_id}"))
+            .await?
+            .json::<ReadRecordResponse>()
+            .await?;
+        if response.status.not_found() {
+            return Ok(None);
+        }
+        response.status.check()?;
+
+        Ok(response.data.map(|data| ReadingProgress {
+            chapter_id: data.chap_id,
+            read_time: data.read_time,
+        }))
+    }
+
+    async fn set_reading_progress(&self, novel_id: u32, chapter_id: u32) -> Result<(), Error> {
+        let response = self
+            .put(
+                format!("/user/readrecords/{novel_id}"),
+                ReadRecordRequest {
+                    chap_id: chapter_id,
                 },
             )
             .await?
@@ -310,6 +601,76 @@ impl Client for SfacgClient {
         Ok(())
     }
 
+    async fn buy_chapter(&self, info: &ChapterInfo) -> Result<(), Error> {
+        self.options
+            .spending
+            .check(self.db().await?, &[info])
+            .await?;
+        if self.options.spending.dry_run(&[info]) {
+            return Ok(());
+        }
+
+        self.order_chapters(info.novel_id.unwrap(), &[info]).await
+    }
+
+    async fn buy_chapters(&self, infos: &[ChapterInfo]) -> Result<Vec<ChapterPurchase>, Error> {
+        let chapter_infos = crate::chapters_to_buy(infos);
+        self.check_purchase(&chapter_infos).await?;
+        if self.options.spending.dry_run(&chapter_infos) {
+            return Ok(crate::dry_run_results(infos, &chapter_infos));
+        }
+
+        // One order for the chapters of each novel
+        let mut orders: Vec<(u32, Vec<&ChapterInfo>)> = Vec::new();
+        for chapter_info in chapter_infos {
+            let novel_id = chapter_info.novel_id.unwrap();
+
+            match orders.iter_mut().find(|(id, _)| *id == novel_id) {
+                Some((_, chapter_infos)) => chapter_infos.push(chapter_info),
+                None => orders.push((novel_id, vec![chapter_info])),
+            }
+        }
+
+        let mut statuses = HashMap::new();
+        for (novel_id, chapter_infos) in orders {
+            let result = self.order_chapters(novel_id, &chapter_infos).await;
+            crate::record_order(&mut statuses, &crate::chapter_ids(&chapter_infos), result);
+        }
+
+        Ok(crate::purchase_results(infos, statuses))
+    }
+
+    async fn buy_volume(&self, info: &VolumeInfo) -> Result<Vec<ChapterPurchase>, Error> {
+        self.buy_chapters(&info.chapter_infos).await
+    }
+
+    async fn buy_all_remaining(&self, novel_id: u32) -> Result<Vec<ChapterPurchase>, Error> {
+        let Some(volume_infos) = self.volume_infos(novel_id).await? else {
+            return Err(Error::NotFound(format!("novel {novel_id}")));
+        };
+        let infos = crate::all_chapters(volume_infos);
+
+        let chapter_infos = crate::chapters_to_buy(&infos);
+        self.check_purchase(&chapter_infos).await?;
+        if self.options.spending.dry_run(&chapter_infos) {
+            return Ok(crate::dry_run_results(&infos, &chapter_infos));
+        }
+
+        // Only the checked chapters are ordered, `order_all` would also buy chapters that are
+        // missing from a cached chapter list
+        let mut statuses = HashMap::new();
+        if !chapter_infos.is_empty() {
+            let result = self.order_chapters(novel_id, &chapter_infos).await;
+            crate::record_order(&mut statuses, &crate::chapter_ids(&chapter_infos), result);
+        }
+
+        Ok(crate::purchase_results(&infos, statuses))
+    }
+
+    async fn purchase_records(&self) -> Result<Vec<PurchaseRecord>, Error> {
+        self.db().await?.find_purchases().await
+    }
+
     async fn image(&self, url: &Url) -> Result<DynamicImage, Error> {
         match self.db().await?.find_image(url).await? {
             FindImageResult::Ok(image) => Ok(image),
@@ -333,6 +694,10 @@ impl Client for SfacgClient {
 
         CATEGORIES
             .get_or_try_init(|| async {
+                if self.options.offline {
+                    return self.db().await?.find_cache(CacheKey::Categories).await;
+                }
+
                 let response = self
                     .get("/noveltypes")
                     .await?
@@ -352,6 +717,11 @@ impl Client for SfacgClient {
 
                 result.sort_unstable_by_key(|x| x.id.unwrap());
 
+                self.db()
+                    .await?
+                    .save_cache(CacheKey::Categories, &result)
+                    .await?;
+
                 Ok(result)
             })
             .await
@@ -361,6 +731,10 @@ impl Client for SfacgClient {
         static TAGS: OnceCell<Vec<Tag>> = OnceCell::const_new();
 
         TAGS.get_or_try_init(|| async {
+            if self.options.offline {
+                return self.db().await?.find_cache(CacheKey::Tags).await;
+            }
+
             let response = self
                 .get("/novels/0/sysTags")
                 .await?
@@ -385,6 +759,8 @@ impl Client for SfacgClient {
 
             result.sort_unstable_by_key(|x| x.id.unwrap());
 
+            self.db().await?.save_cache(CacheKey::Tags, &result).await?;
+
             Ok(result)
         })
         .await
@@ -396,7 +772,159 @@ impl Client for SfacgClient {
         page: u16,
         size: u16,
     ) -> Result<Option<Vec<u32>>, Error> {
-        assert!(size <= 50, "The maximum number of items per page is 50");
+        assert!(
+            size <= SfacgClient::MAX_PAGE_SIZE,
+            "The maximum number of items per page is 50"
+        );
+
+        Ok(self
+            .do_search(option, page, size)
+            .await?
+            .map(|summaries| summaries.into_iter().map(|summary| summary.id).collect()))
+    }
+
+    async fn search_summaries(
+        &self,
+        option: &Options,
+        page: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        self.do_search(option, page, SfacgClient::MAX_PAGE_SIZE)
+            .await
+    }
+
+    async fn rankings(
+        &self,
+        kind: RankingKind,
+        period: RankingPeriod,
+        page: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        let period = match period {
+            RankingPeriod::Week => "week",
+            RankingPeriod::Month => "month",
+            RankingPeriod::Total => "all",
+        };
+
+        // view 点击榜
+        // ticket 月票榜
+        // newhit 新书榜
+        // sale 畅销榜
+        let rtype = match kind {
+            RankingKind::Popular => "view",
+            RankingKind::Tickets => "ticket",
+            RankingKind::NewBooks => "newhit",
+            RankingKind::Bestsellers => "sale",
+        };
+
+        let response = self
+            .get_query(
+                format!("/ranks/{period}/novels"),
+                RanksRequest {
+                    rtype,
+                    page,
+                    size: SfacgClient::MAX_PAGE_SIZE,
+                    expand: "sysTags",
+                },
+            )
+            .await?
+            .json::<NovelsResponse>()
+            .await?;
+        response.status.check()?;
+        let data = response.data.unwrap();
+
+        if data.is_empty() {
+            return Ok(None);
+        }
+
+        let mut result = Vec::new();
+        for novel_data in data {
+            result.push(self.parse_summary(novel_data).await?);
+        }
+
+        Ok(Some(result))
+    }
+}
+
+impl SfacgClient {
+    const MAX_PAGE_SIZE: u16 = 50;
+
+    /// Check the spending policy and the balance before the chapters are bought
+    async fn check_purchase(&self, chapter_infos: &[&ChapterInfo]) -> Result<(), Error> {
+        if chapter_infos.is_empty() {
+            return Ok(());
+        }
+
+        let spending = &self.options.spending;
+        spending.check(self.db().await?, chapter_infos).await?;
+
+        let result = self
+            .money()
+            .await
+            .and_then(|money| crate::check_balance(chapter_infos, money));
+        if result.is_err() {
+            spending.release(chapter_infos).await;
+        }
+
+        result
+    }
+
+    /// Order the chapters, the price reserved by the spending policy is released on failure
+    async fn order_chapters(
+        &self,
+        novel_id: u32,
+        chapter_infos: &[&ChapterInfo],
+    ) -> Result<(), Error> {
+        if let Err(err) = self.post_order(novel_id, chapter_infos).await {
+            self.options.spending.release(chapter_infos).await;
+            return Err(err);
+        }
+
+        let db = self.db().await?;
+        // The account has been charged, the purchase must not be reported as failed
+        if let Err(err) = self.options.spending.record(db, chapter_infos).await {
+            error!(
+                "Fail to save the purchase of chapters {:?}: {err}",
+                crate::chapter_ids(chapter_infos)
+            );
+        }
+        for chapter_info in chapter_infos {
+            if let Err(err) = db.set_chapter_paid(chapter_info.id).await {
+                error!("Fail to mark chapter {} as paid: {err}", chapter_info.id);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn post_order(&self, novel_id: u32, chapter_infos: &[&ChapterInfo]) -> Result<(), Error> {
+        let response = self
+            .post(
+                &format!("/novels/{novel_id}/orderedchaps"),
+                BuyChapterRequest {
+                    order_all: false,
+                    auto_order: false,
+                    chap_ids: crate::chapter_ids(chapter_infos),
+                    order_type: "readOrder",
+                },
+            )
+            .await?
+            .json::<GenericResponse>()
+            .await?;
+        response.status.check()
+    }
+
+    async fn do_search(
+        &self,
+        option: &Options,
+        page: u16,
+        size: u16,
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        // The server can not filter by author
+        if option.author.is_some() {
+            return Err(Error::Unsupported(format!(
+                "{}: author filter",
+                SfacgClient::APP_NAME
+            )));
+        }
 
         if option.keyword.is_some() {
             self.do_search_with_keyword(option, page, size).await
@@ -404,15 +932,15 @@ impl Client for SfacgClient {
             self.do_search_without_keyword(option, page, size).await
         }
     }
-}
 
-impl SfacgClient {
     async fn do_search_with_keyword(
         &self,
         option: &Options,
         page: u16,
         size: u16,
-    ) -> Result<Option<Vec<u32>>, Error> {
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        let sort = SfacgClient::to_search_sort(option)?;
+
         // 0 连载中
         // 1 已完结
         // -1 不限
@@ -441,12 +969,7 @@ impl SfacgClient {
                     systagids: SfacgClient::tag_ids(&option.tags),
                     page,
                     size,
-                    // hot 人气最高
-                    // update 最新更新
-                    // marknum 收藏最高
-                    // ticket 月票最多
-                    // charcount 更新最多
-                    sort: "hot",
+                    sort,
                     expand: "sysTags",
                 },
             )
@@ -466,7 +989,7 @@ impl SfacgClient {
         for novel_info in data.novels {
             let mut tag_ids = vec![];
 
-            for tag in novel_info.expand.sys_tags {
+            for tag in &novel_info.expand.sys_tags {
                 if let Some(sys_tag) = sys_tags.iter().find(|x| x.id.unwrap() == tag.sys_tag_id) {
                     tag_ids.push(sys_tag.id.unwrap());
                 }
@@ -477,7 +1000,7 @@ impl SfacgClient {
                 && SfacgClient::match_vip(option, &novel_info.sign_status)
                 && SfacgClient::match_word_count(option, novel_info.char_count)
             {
-                result.push(novel_info.novel_id);
+                result.push(self.parse_summary(novel_info).await?);
             }
         }
 
@@ -489,7 +1012,9 @@ impl SfacgClient {
         option: &Options,
         page: u16,
         size: u16,
-    ) -> Result<Option<Vec<u32>>, Error> {
+    ) -> Result<Option<Vec<NovelSummary>>, Error> {
+        let sort = SfacgClient::to_novels_sort(option)?;
+
         let mut category_id = 0;
         if option.category.is_some() {
             category_id = option.category.as_ref().unwrap().id.unwrap();
@@ -535,12 +1060,8 @@ impl SfacgClient {
                     updatedays,
                     page,
                     size,
-                    // latest 最新更新
-                    // viewtimes 人气最高
-                    // bookmark 收藏最高
-                    // ticket 月票最多
-                    // charcount 更新最多
-                    sort: "viewtimes",
+                    sort,
+                    expand: "sysTags",
                 },
             )
             .await?
@@ -555,12 +1076,57 @@ impl SfacgClient {
 
         let mut result = Vec::new();
         for novel_data in data {
-            result.push(novel_data.novel_id);
+            result.push(self.parse_summary(novel_data).await?);
         }
 
         Ok(Some(result))
     }
 
+    async fn parse_summary(&self, novel_data: NovelsData) -> Result<NovelSummary, Error> {
+        Ok(NovelSummary {
+            id: novel_data.novel_id,
+            name: novel_data.novel_name.trim().to_string(),
+            author_name: Some(novel_data.author_name.trim().to_string()),
+            cover_url: Some(novel_data.novel_cover),
+            word_count: SfacgClient::parse_word_count(novel_data.char_count),
+            is_vip: Some(novel_data.sign_status == "VIP"),
+            is_finished: Some(novel_data.is_finish),
+            update_time: Some(novel_data.last_update_time),
+            category: self.find_category(novel_data.type_id).await?,
+            tags: self.parse_tags(novel_data.expand.sys_tags).await?,
+        })
+    }
+
+    fn parse_comments(comments: Vec<CommentData>) -> Option<Vec<Comment>> {
+        if comments.is_empty() {
+            return None;
+        }
+
+        Some(
+            comments
+                .into_iter()
+                .map(|comment| Comment {
+                    id: comment.cmt_id,
+                    author_name: comment.user_name.trim().to_string(),
+                    author_avatar: comment.avatar,
+                    content: comment.content.trim().to_string(),
+                    create_time: Some(comment.create_time),
+                    like_count: Some(comment.praise_num),
+                    reply_count: Some(comment.reply_num),
+                })
+                .collect(),
+        )
+    }
+
+    async fn find_category(&self, type_id: u16) -> Result<Option<Category>, Error> {
+        Ok(self
+            .categories()
+            .await?
+            .iter()
+            .find(|category| category.id == Some(type_id))
+            .cloned())
+    }
+
     fn parse_word_count(word_count: i32) -> Option<u32> {
         // Some novels have negative word counts
         if word_count <= 0 {
@@ -637,6 +1203,32 @@ impl SfacgClient {
         }
     }
 
+    fn to_search_sort(option: &Options) -> Result<&'static str, Error> {
+        match option.sort {
+            None | Some(SortOrder::Popular) => Ok("hot"),
+            Some(SortOrder::Latest) => Ok("update"),
+            Some(SortOrder::Bookmarks) => Ok("marknum"),
+            Some(SortOrder::Tickets) => Ok("ticket"),
+            Some(SortOrder::WordCount) => Ok("charcount"),
+            Some(sort) => Err(SfacgClient::unsupported_sort(sort)),
+        }
+    }
+
+    fn to_novels_sort(option: &Options) -> Result<&'static str, Error> {
+        match option.sort {
+            None | Some(SortOrder::Popular) => Ok("viewtimes"),
+            Some(SortOrder::Latest) => Ok("latest"),
+            Some(SortOrder::Bookmarks) => Ok("bookmark"),
+            Some(SortOrder::Tickets) => Ok("ticket"),
+            Some(SortOrder::WordCount) => Ok("charcount"),
+            Some(sort) => Err(SfacgClient::unsupported_sort(sort)),
+        }
+    }
+
+    fn unsupported_sort(sort: SortOrder) -> Error {
+        Error::Unsupported(format!("{}: sort order {sort:?}", SfacgClient::APP_NAME))
+    }
+
     fn bool_to_str(flag: &Option<bool>) -> &'static str {
         if flag.is_some() {
             if *flag.as_ref().unwrap() {
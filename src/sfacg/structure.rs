@@ -48,15 +48,30 @@ impl Status {
         self.http_code == StatusCode::EXPECTATION_FAILED && self.error_code == 1116
     }
 
+    #[must_use]
+    pub(crate) fn too_many_requests(&self) -> bool {
+        self.http_code == StatusCode::TOO_MANY_REQUESTS
+    }
+
     pub(crate) fn check(self) -> Result<(), Error> {
-        if !(self.ok() || self.created()) {
-            return Err(Error::Http {
-                code: StatusCode::from_u16(self.http_code)?,
-                msg: self.msg.unwrap().trim().to_string(),
-            })?;
+        if self.ok() || self.created() {
+            return Ok(());
         }
 
-        Ok(())
+        let msg = self.msg.as_deref().unwrap_or_default().trim().to_string();
+
+        if self.unauthorized() {
+            Err(Error::LoginExpired)
+        } else if self.not_found() {
+            Err(Error::NotFound(msg))
+        } else if self.too_many_requests() {
+            Err(Error::RateLimited(msg))
+        } else {
+            Err(Error::from_server(
+                format!("{}/{}", self.http_code, self.error_code),
+                msg,
+            ))
+        }
     }
 }
 
@@ -126,7 +141,10 @@ pub(crate) struct BookshelfInfoResponse {
 
 #[must_use]
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub(crate) struct BookshelfInfoData {
+    pub pocket_id: u32,
+    pub name: String,
     pub expand: Option<BookshelfInfoExpand>,
 }
 
@@ -143,6 +161,13 @@ pub(crate) struct BookshelfInfoNovelInfo {
     pub novel_id: u32,
 }
 
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AddToBookshelfRequest {
+    pub novel_id: u32,
+}
+
 #[must_use]
 #[derive(Serialize)]
 pub(crate) struct NovelInfoRequest {
@@ -162,6 +187,7 @@ pub(crate) struct NovelInfoResponse {
 pub(crate) struct NovelInfoData {
     pub novel_name: String,
     pub novel_cover: Url,
+    pub author_id: u32,
     pub author_name: String,
     pub char_count: i32,
     pub type_id: u16,
@@ -254,6 +280,102 @@ pub(crate) struct ContentInfosExpand {
     pub is_content_encrypted: bool,
 }
 
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct CommentsRequest {
+    pub page: u16,
+    pub size: u16,
+    pub sort: &'static str,
+}
+
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParagraphCommentsRequest {
+    pub paragraph_index: usize,
+    pub page: u16,
+    pub size: u16,
+    pub sort: &'static str,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct CommentsResponse {
+    pub status: Status,
+    pub data: Option<Vec<CommentData>>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CommentData {
+    pub cmt_id: u32,
+    pub content: String,
+    pub create_time: NaiveDateTime,
+    pub user_name: String,
+    pub avatar: Option<Url>,
+    pub praise_num: u32,
+    pub reply_num: u32,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ReviewsResponse {
+    pub status: Status,
+    pub data: Option<Vec<ReviewData>>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReviewData {
+    pub cmt_id: u32,
+    pub title: Option<String>,
+    pub content: String,
+    pub create_time: NaiveDateTime,
+    pub user_name: String,
+    pub avatar: Option<Url>,
+    pub praise_num: u32,
+    pub reply_num: u32,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ParagraphCommentCountsResponse {
+    pub status: Status,
+    pub data: Option<Vec<ParagraphCommentCount>>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ParagraphCommentCount {
+    pub paragraph_index: usize,
+    pub count: u32,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct ReadRecordResponse {
+    pub status: Status,
+    pub data: Option<ReadRecordData>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReadRecordData {
+    pub chap_id: u32,
+    pub read_time: Option<NaiveDateTime>,
+}
+
+#[must_use]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReadRecordRequest {
+    pub chap_id: u32,
+}
+
 #[must_use]
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -319,18 +441,7 @@ pub(crate) struct SearchResponse {
 #[must_use]
 #[derive(Deserialize)]
 pub(crate) struct SearchData {
-    pub novels: Vec<SearchNovelInfo>,
-}
-
-#[must_use]
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub(crate) struct SearchNovelInfo {
-    pub novel_id: u32,
-    pub sign_status: String,
-    pub char_count: i32,
-    pub type_id: u16,
-    pub expand: SearchExpand,
+    pub novels: Vec<NovelsData>,
 }
 
 #[must_use]
@@ -355,6 +466,7 @@ pub(crate) struct NovelsRequest {
     pub page: u16,
     pub size: u16,
     pub sort: &'static str,
+    pub expand: &'static str,
 }
 
 #[must_use]
@@ -369,4 +481,46 @@ pub(crate) struct NovelsResponse {
 #[serde(rename_all = "camelCase")]
 pub(crate) struct NovelsData {
     pub novel_id: u32,
+    pub novel_name: String,
+    pub novel_cover: Url,
+    pub author_name: String,
+    pub sign_status: String,
+    pub char_count: i32,
+    pub type_id: u16,
+    pub is_finish: bool,
+    pub last_update_time: NaiveDateTime,
+    pub expand: SearchExpand,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+pub(crate) struct AuthorResponse {
+    pub status: Status,
+    pub data: Option<AuthorData>,
+}
+
+#[must_use]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuthorData {
+    pub author_id: u32,
+    pub author_name: String,
+    pub avatar: Option<Url>,
+    pub intro: Option<String>,
+    pub fans_num: u32,
+}
+
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct AuthorNovelsRequest {
+    pub expand: &'static str,
+}
+
+#[must_use]
+#[derive(Serialize)]
+pub(crate) struct RanksRequest {
+    pub rtype: &'static str,
+    pub page: u16,
+    pub size: u16,
+    pub expand: &'static str,
 }
@@ -4,11 +4,10 @@ use hex_simd::AsciiCase;
 use reqwest::{header::HeaderValue, Response};
 use serde::Serialize;
 use tokio::sync::OnceCell;
-use tracing::info;
 use url::Url;
 use uuid::Uuid;
 
-use crate::{Error, HTTPClient, NovelDB, SfacgClient};
+use crate::{ClientBuilder, Error, Freshness, HTTPClient, NovelDB, SfacgClient};
 
 #[cfg(target_os = "windows")]
 macro_rules! PATH_SEPARATOR {
@@ -27,7 +26,7 @@ macro_rules! PATH_SEPARATOR {
 include!(concat!(env!("OUT_DIR"), PATH_SEPARATOR!(), "codegen.rs"));
 
 impl SfacgClient {
-    const APP_NAME: &'static str = "sfacg";
+    pub(crate) const APP_NAME: &'static str = "sfacg";
 
     const HOST: &'static str = "https://api.sfacg.com";
     const USER_AGENT: &'static str = "boluobao/5.0.66(android;31)/H5/{}/H5";
@@ -41,19 +40,33 @@ impl SfacgClient {
 
     /// Create a sfacg client
     pub async fn new() -> Result<Self, Error> {
-        Ok(Self {
-            proxy: None,
-            no_proxy: false,
-            cert_path: None,
-            client: OnceCell::new(),
-            client_rss: OnceCell::new(),
-            db: OnceCell::new(),
-        })
+        Self::builder().build().await
+    }
+
+    /// Create a builder of sfacg clients
+    pub fn builder() -> ClientBuilder<Self> {
+        ClientBuilder::default()
+    }
+
+    /// In offline mode the saved information is always used
+    pub(crate) fn metadata_freshness(&self) -> Freshness {
+        if self.options.offline {
+            Freshness::Forever
+        } else {
+            self.options.freshness
+        }
     }
 
     pub(crate) async fn db(&self) -> Result<&NovelDB, Error> {
         self.db
-            .get_or_try_init(|| async { NovelDB::new(SfacgClient::APP_NAME).await })
+            .get_or_try_init(|| async {
+                NovelDB::new(
+                    SfacgClient::APP_NAME,
+                    &self.options.profile,
+                    &self.options.storage,
+                )
+                .await
+            })
             .await
     }
 
@@ -68,9 +81,8 @@ impl SfacgClient {
                     .add_header("accept-charset", HeaderValue::from_static("UTF-8"))
                     .cookie(true)
                     .user_agent(user_agent)
-                    .proxy(self.proxy.clone())
-                    .no_proxy(self.no_proxy)
-                    .cert(self.cert_path.clone())
+                    .options(&self.options)
+                    .rate_limit(self.options.rate_limit)
                     .build()
                     .await
             })
@@ -82,9 +94,8 @@ impl SfacgClient {
             .get_or_try_init(|| async {
                 HTTPClient::builder(SfacgClient::APP_NAME)
                     .user_agent(SfacgClient::USER_AGENT_RSS)
-                    .proxy(self.proxy.clone())
-                    .no_proxy(self.no_proxy)
-                    .cert(self.cert_path.clone())
+                    .options(&self.options)
+                    .rate_limit(self.options.image_rate_limit)
                     .build()
                     .await
             })
@@ -95,14 +106,16 @@ impl SfacgClient {
     where
         T: AsRef<str>,
     {
-        Ok(self
-            .client()
-            .await?
-            .get(SfacgClient::HOST.to_string() + url.as_ref())
-            .basic_auth(SfacgClient::USERNAME, Some(SfacgClient::PASSWORD))
-            .header("sfsecurity", self.sf_security()?)
-            .send()
-            .await?)
+        let client = self.client().await?;
+
+        client
+            .send(
+                client
+                    .get(self.api_url(&url))
+                    .basic_auth(SfacgClient::USERNAME, Some(SfacgClient::PASSWORD))
+                    .header("sfsecurity", self.sf_security()?),
+            )
+            .await
     }
 
     pub(crate) async fn get_query<T, E>(&self, url: T, query: E) -> Result<Response, Error>
@@ -110,36 +123,17 @@ impl SfacgClient {
         T: AsRef<str>,
         E: Serialize,
     {
-        let mut count = 0;
-
-        let response = loop {
-            let response = self
-                .client()
-                .await?
-                .get(SfacgClient::HOST.to_string() + url.as_ref())
-                .query(&query)
-                .basic_auth(SfacgClient::USERNAME, Some(SfacgClient::PASSWORD))
-                .header("sfsecurity", self.sf_security()?)
-                .send()
-                .await;
-
-            if let Ok(response) = response {
-                break response;
-            } else {
-                info!(
-                    "HTTP request failed: `{}`, retry, number of times: `{}`",
-                    response.as_ref().unwrap_err(),
-                    count + 1
-                );
-
-                count += 1;
-                if count > 3 {
-                    response?;
-                }
-            }
-        };
-
-        Ok(response)
+        let client = self.client().await?;
+
+        client
+            .send(
+                client
+                    .get(self.api_url(&url))
+                    .query(&query)
+                    .basic_auth(SfacgClient::USERNAME, Some(SfacgClient::PASSWORD))
+                    .header("sfsecurity", self.sf_security()?),
+            )
+            .await
     }
 
     pub(crate) async fn post<T, E>(&self, url: T, json: E) -> Result<Response, Error>
@@ -147,15 +141,17 @@ impl SfacgClient {
         T: AsRef<str>,
         E: Serialize,
     {
-        Ok(self
-            .client()
-            .await?
-            .post(SfacgClient::HOST.to_string() + url.as_ref())
-            .basic_auth(SfacgClient::USERNAME, Some(SfacgClient::PASSWORD))
-            .header("sfsecurity", self.sf_security()?)
-            .json(&json)
-            .send()
-            .await?)
+        let client = self.client().await?;
+
+        client
+            .send(
+                client
+                    .post(self.api_url(&url))
+                    .basic_auth(SfacgClient::USERNAME, Some(SfacgClient::PASSWORD))
+                    .header("sfsecurity", self.sf_security()?)
+                    .json(&json),
+            )
+            .await
     }
 
     pub(crate) async fn put<T, E>(&self, url: T, json: E) -> Result<Response, Error>
@@ -163,24 +159,55 @@ impl SfacgClient {
         T: AsRef<str>,
         E: Serialize,
     {
-        Ok(self
-            .client()
-            .await?
-            .put(SfacgClient::HOST.to_string() + url.as_ref())
-            .basic_auth(SfacgClient::USERNAME, Some(SfacgClient::PASSWORD))
-            .header("sfsecurity", self.sf_security()?)
-            .json(&json)
-            .send()
-            .await?)
+        let client = self.client().await?;
+
+        client
+            .send(
+                client
+                    .put(self.api_url(&url))
+                    .basic_auth(SfacgClient::USERNAME, Some(SfacgClient::PASSWORD))
+                    .header("sfsecurity", self.sf_security()?)
+                    .json(&json),
+            )
+            .await
+    }
+
+    pub(crate) async fn delete<T>(&self, url: T) -> Result<Response, Error>
+    where
+        T: AsRef<str>,
+    {
+        let client = self.client().await?;
+
+        client
+            .send(
+                client
+                    .delete(self.api_url(&url))
+                    .basic_auth(SfacgClient::USERNAME, Some(SfacgClient::PASSWORD))
+                    .header("sfsecurity", self.sf_security()?),
+            )
+            .await
     }
 
     pub(crate) async fn get_rss(&self, url: &Url) -> Result<Response, Error> {
-        let response = self.client_rss().await?.get(url.clone()).send().await?;
+        let client = self.client_rss().await?;
+        let response = client.send(client.get(url.clone())).await?;
         crate::check_status(response.status(), format!("HTTP request failed: `{url}`"))?;
 
         Ok(response)
     }
 
+    fn api_url<T>(&self, url: T) -> String
+    where
+        T: AsRef<str>,
+    {
+        let host = match &self.options.host {
+            Some(host) => host.as_str().trim_end_matches('/'),
+            None => SfacgClient::HOST,
+        };
+
+        format!("{host}{}", url.as_ref())
+    }
+
     fn sf_security(&self) -> Result<String, Error> {
         let uuid = Uuid::new_v4();
         let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
@@ -206,3 +233,17 @@ impl SfacgClient {
         result
     }
 }
+
+impl ClientBuilder<SfacgClient> {
+    /// Check the options and create the client
+    pub async fn build(self) -> Result<SfacgClient, Error> {
+        self.options.validate().await?;
+
+        Ok(SfacgClient {
+            options: self.options,
+            client: OnceCell::new(),
+            client_rss: OnceCell::new(),
+            db: OnceCell::new(),
+        })
+    }
+}
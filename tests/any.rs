@@ -0,0 +1,37 @@
+mod common;
+
+use anyhow::Result;
+use axum::Router;
+use novel_api::{AnyClient, Client, Source, Storage, Target};
+use pretty_assertions::assert_eq;
+
+use self::common::MockServer;
+
+#[tokio::test]
+async fn any_client() -> Result<()> {
+    let app = Router::new()
+        .fallback(|| async { common::json_response(common::fixture("sfacg", "money.json")) });
+    let server = MockServer::start(app).await?;
+
+    let route = novel_api::route("https://book.sfacg.com/Novel/263060/")?;
+    assert_eq!(route.target, Target::Novel(263060));
+
+    let dir = tempfile::tempdir()?;
+    let client = AnyClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .storage(Storage::root(dir.path()))
+        .build(route.source)
+        .await?;
+    assert_eq!(client.source(), Source::Sfacg);
+    assert_eq!(client.money().await?, 120);
+
+    // The clients of all sources can be stored together
+    let clients: Vec<Box<AnyClient>> = vec![Box::new(client)];
+    assert_eq!(clients[0].money().await?, 120);
+
+    assert_eq!("ciweimao".parse::<Source>()?, Source::Ciweimao);
+    assert_eq!(Source::Ciyuanji.to_string(), "ciyuanji");
+
+    Ok(())
+}
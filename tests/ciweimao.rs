@@ -0,0 +1,630 @@
+mod common;
+
+use aes::{
+    cipher::{block_padding::Pkcs7, BlockEncryptMut, KeyIvInit},
+    Aes256,
+};
+use anyhow::Result;
+use axum::{
+    http::{Method, Uri},
+    response::Response,
+    Router,
+};
+use novel_api::{
+    Cassette, ChapterInfo, CiweimaoClient, Client, ContentInfo, Error, KeySource, Options, Profile,
+    PurchaseStatus, RankingKind, RankingPeriod, Source, Storage, Tag,
+};
+use pretty_assertions::assert_eq;
+use ring::digest;
+use tempfile::TempDir;
+use url::form_urlencoded;
+
+use self::common::MockServer;
+
+const SOURCE: &str = "ciweimao";
+
+const AES_KEY: &str = "zG2nSeEfSHfvTCHy5LCcqtBbQehKNLXn";
+const CHAPTER_COMMAND: &str = "mock-chapter-command";
+
+fn aes_256_cbc_no_iv_base64_encrypt<T>(key: T, data: &str) -> String
+where
+    T: AsRef<str>,
+{
+    let key = digest::digest(&digest::SHA256, key.as_ref().as_bytes());
+
+    type Aes256CbcEnc = cbc::Encryptor<Aes256>;
+    let encrypted = Aes256CbcEnc::new(key.as_ref().into(), &[0; 16].into())
+        .encrypt_padded_vec_mut::<Pkcs7>(data.as_bytes());
+
+    base64_simd::STANDARD.encode_to_string(encrypted)
+}
+
+async fn handler(method: Method, uri: Uri, body: String) -> Response {
+    let path = uri.path();
+
+    if path.starts_with("/images/") {
+        return common::image_response();
+    }
+
+    if method != Method::POST {
+        return common::not_found();
+    }
+
+    let form: Vec<(String, String)> = form_urlencoded::parse(body.as_bytes())
+        .into_owned()
+        .collect();
+    let param = |name: &str| {
+        form.iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    // Parameters appended to every request
+    assert!(param("app_version").is_some());
+    assert!(param("device_token").is_some());
+    assert_eq!(param("rand_str").map(|x| x.len()), Some(32));
+    assert!(param("p").is_some());
+
+    let name = match path {
+        "/reader/get_my_info" => "get_my_info.json",
+        "/reader/get_prop_info" => "get_prop_info.json",
+        "/reader/get_task_bonus_with_sign_recommend" => "generic.json",
+        "/bookshelf/get_shelf_list" => "get_shelf_list.json",
+        "/bookshelf/get_shelf_book_list_new" if param("shelf_id") == Some("1") => {
+            "get_shelf_book_list_new_1.json"
+        }
+        "/bookshelf/get_shelf_book_list_new" => "get_shelf_book_list_new_2.json",
+        "/bookshelf/favor" | "/bookshelf/delete_shelf_book" => {
+            assert_eq!(param("book_id"), Some("100041352"));
+            "generic.json"
+        }
+        "/bookshelf/move_shelf_books" => {
+            assert_eq!(param("from_shelf_id"), Some("1"));
+            assert_eq!(param("to_shelf_id"), Some("2"));
+            "generic.json"
+        }
+        "/book/get_info_by_id" if param("book_id") == Some("100041352") => "get_info_by_id.json",
+        "/book/get_info_by_id" => "not_found.json",
+        "/author/get_author_info" if param("author_id") == Some("1001") => "get_author_info.json",
+        "/author/get_author_info" => "not_found.json",
+        "/author/get_author_book_list" => "get_author_book_list.json",
+        "/chapter/get_updated_chapter_by_division_new" => {
+            "get_updated_chapter_by_division_new.json"
+        }
+        "/chapter/get_chapter_permission_list" => "get_chapter_permission_list.json",
+        "/chapter/get_chapter_cmd" => "get_chapter_cmd.json",
+        "/chapter/get_cpt_ifm" => {
+            assert_eq!(param("chapter_command"), Some(CHAPTER_COMMAND));
+
+            let content = format!(
+                "  第一段  \n\n<img src=\"{}\"/>\n第二段",
+                common::image_url("/images/1.png")
+            );
+            let txt_content = aes_256_cbc_no_iv_base64_encrypt(CHAPTER_COMMAND, &content);
+
+            let json =
+                common::fixture(SOURCE, "get_cpt_ifm.json").replace("{txt_content}", &txt_content);
+            return common::json_response(aes_256_cbc_no_iv_base64_encrypt(AES_KEY, &json));
+        }
+        "/chapter/buy" => "generic.json",
+        "/chapter/get_chapter_comment_list" if param("page") == Some("0") => {
+            "get_chapter_comment_list.json"
+        }
+        "/chapter/get_chapter_comment_list" => "empty_comment_list.json",
+        "/book/get_review_list" if param("page") == Some("0") => "get_review_list.json",
+        "/book/get_review_list" => "empty_review_list.json",
+        "/book/get_review_comment_list" if param("page") == Some("0") => {
+            "get_review_comment_list.json"
+        }
+        "/book/get_review_comment_list" => "empty_review_comment_list.json",
+        "/chapter/get_tsukkomi_num" => "get_tsukkomi_num.json",
+        "/chapter/get_paragraph_tsukkomi_list_new" => {
+            assert_eq!(param("paragraph_index"), Some("2"));
+
+            if param("page") == Some("0") {
+                "get_paragraph_tsukkomi_list_new.json"
+            } else {
+                "empty_tsukkomi_list.json"
+            }
+        }
+        "/bookshelf/get_last_read_chapter" if param("book_id") == Some("100041352") => {
+            "get_last_read_chapter.json"
+        }
+        "/bookshelf/get_last_read_chapter" => "get_last_read_chapter_unread.json",
+        "/bookshelf/set_last_read_chapter" => {
+            assert_eq!(param("last_read_chapter_id"), Some("9000003"));
+            "generic.json"
+        }
+        "/meta/get_meta_data" => "get_meta_data.json",
+        "/book/get_official_tag_list" => "get_official_tag_list.json",
+        "/bookcity/get_filter_search_book_list" if param("page") == Some("0") => {
+            "get_filter_search_book_list.json"
+        }
+        "/bookcity/get_filter_search_book_list" => "empty_search.json",
+        "/bookcity/get_rank_book_list" if param("page") == Some("0") => "get_rank_book_list.json",
+        "/bookcity/get_rank_book_list" => "empty_search.json",
+        "/signup/use_geetest" => "use_geetest.json",
+        "/signup/login" => "login.json",
+        _ => return common::not_found(),
+    };
+
+    let json = common::fixture(SOURCE, name);
+    common::json_response(aes_256_cbc_no_iv_base64_encrypt(AES_KEY, &json))
+}
+
+async fn client() -> Result<(MockServer, TempDir, CiweimaoClient)> {
+    let server = MockServer::start(Router::new().fallback(handler)).await?;
+    let dir = tempfile::tempdir()?;
+
+    let client = CiweimaoClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .storage(Storage::root(dir.path()))
+        .build()
+        .await?;
+
+    Ok((server, dir, client))
+}
+
+#[tokio::test]
+async fn user() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let user_info = client.user_info().await?;
+    assert_eq!(user_info.nickname, "tester");
+    assert_eq!(
+        user_info.avatar,
+        Some(common::image_url("/images/avatar.png"))
+    );
+
+    assert_eq!(client.money().await?, 120);
+    client.sign_in().await?;
+    assert_eq!(client.bookshelf_infos().await?, vec![100041352, 100041353]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn bookshelf() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let bookshelves = client.bookshelves().await?;
+    assert_eq!(
+        bookshelves
+            .iter()
+            .map(|bookshelf| (bookshelf.id, bookshelf.name.as_str()))
+            .collect::<Vec<_>>(),
+        vec![(1, "默认书架"), (2, "追更")]
+    );
+    assert_eq!(bookshelves[0].novel_ids, vec![100041352]);
+    assert_eq!(bookshelves[1].novel_ids, vec![100041353]);
+
+    client.add_to_bookshelf(100041352, 2).await?;
+    client.remove_from_bookshelf(100041352, 1).await?;
+    client.move_between_shelves(100041352, 1, 2).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn novel() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let novel_info = client.novel_info(100041352).await?.unwrap();
+    assert_eq!(novel_info.name, "测试小说");
+    assert_eq!(novel_info.author_id, Some(1001));
+    assert_eq!(novel_info.author_name, "测试作者");
+    assert_eq!(
+        novel_info.introduction,
+        Some(vec![String::from("第一行简介"), String::from("第二行简介")])
+    );
+    assert_eq!(novel_info.word_count, Some(123456));
+    assert_eq!(novel_info.is_vip, Some(true));
+    assert_eq!(novel_info.is_finished, Some(false));
+    assert_eq!(novel_info.category.unwrap().name, "灵异未知");
+    assert_eq!(
+        novel_info
+            .tags
+            .unwrap()
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect::<Vec<_>>(),
+        vec![String::from("百合")]
+    );
+
+    assert!(client.novel_info(1).await?.is_none());
+
+    let volume_infos = client.volume_infos(100041352).await?.unwrap();
+    assert_eq!(volume_infos.len(), 1);
+    assert_eq!(volume_infos[0].title, "第一卷");
+
+    let chapter_infos = &volume_infos[0].chapter_infos;
+    assert_eq!(chapter_infos.len(), 3);
+    assert!(chapter_infos[0].can_download());
+    assert!(!chapter_infos[1].can_download());
+    assert_eq!(chapter_infos[1].price, Some(32));
+    // The price of a chapter that failed the review does not exist
+    assert!(!chapter_infos[2].is_valid());
+
+    let content_infos = client.content_infos(&chapter_infos[0]).await?;
+    assert_eq!(content_infos.len(), 3);
+    assert!(matches!(&content_infos[0], ContentInfo::Text(text) if text == "第一段"));
+    assert!(matches!(&content_infos[1], ContentInfo::Image(url) if url.path() == "/images/1.png"));
+    assert!(matches!(&content_infos[2], ContentInfo::Text(text) if text == "第二段"));
+
+    client.buy_chapter(&chapter_infos[1]).await?;
+
+    let image = client.image(&common::image_url("/images/1.png")).await?;
+    assert_eq!(image.width(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn purchase() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let volume_infos = client.volume_infos(100041352).await?.unwrap();
+    let purchases = client.buy_volume(&volume_infos[0]).await?;
+    assert_eq!(
+        purchases
+            .iter()
+            .map(|purchase| (purchase.chapter_id, &purchase.status))
+            .collect::<Vec<_>>(),
+        vec![
+            (9000001, &PurchaseStatus::Skipped),
+            (9000002, &PurchaseStatus::Bought),
+            // Invalid chapters can not be bought
+            (9000003, &PurchaseStatus::Skipped)
+        ]
+    );
+
+    // The balance is checked before anything is bought
+    let chapter_info = ChapterInfo {
+        id: 9000002,
+        price: Some(200),
+        ..Default::default()
+    };
+    assert!(matches!(
+        client.buy_chapters(&[chapter_info]).await,
+        Err(Error::InsufficientBalance(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn comments() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let volume_infos = client.volume_infos(100041352).await?.unwrap();
+    let chapter_info = &volume_infos[0].chapter_infos[0];
+
+    let comments = client.chapter_comments(chapter_info, 0).await?.unwrap();
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].id, 501);
+    assert_eq!(comments[0].author_name, "读者");
+    assert_eq!(comments[0].content, "写得真好");
+    assert_eq!(comments[0].like_count, Some(12));
+    assert_eq!(comments[0].reply_count, Some(3));
+    assert_eq!(client.chapter_comments(chapter_info, 1).await?, None);
+
+    // The second text paragraph follows an image
+    let counts = client.paragraph_comment_counts(chapter_info).await?;
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts[&0], 3);
+    assert_eq!(counts[&2], 5);
+
+    let comments = client
+        .paragraph_comments(chapter_info, 2, 0)
+        .await?
+        .unwrap();
+    assert_eq!(comments[0].id, 601);
+    assert_eq!(comments[0].author_avatar.is_some(), false);
+    assert_eq!(client.paragraph_comments(chapter_info, 2, 1).await?, None);
+    assert!(matches!(
+        client.paragraph_comments(chapter_info, 1, 0).await,
+        Err(Error::NotFound(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn author() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let author = client.author_info(1001).await?.unwrap();
+    assert_eq!(author.name, "测试作者");
+    assert_eq!(author.avatar, None);
+    assert_eq!(
+        author.introduction,
+        Some(vec![String::from("第一行介绍"), String::from("第二行介绍")])
+    );
+    assert_eq!(author.follower_count, Some(321));
+    assert_eq!(client.author_info(1).await?, None);
+
+    let novels = client.author_novels(1001).await?;
+    assert_eq!(novels.len(), 1);
+    assert_eq!(novels[0].id, 100041352);
+    assert_eq!(novels[0].author_name.as_deref(), Some("测试作者"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn reviews() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let reviews = client.novel_reviews(100041352, 0).await?.unwrap();
+    assert_eq!(
+        reviews.iter().map(|review| review.id).collect::<Vec<_>>(),
+        vec![701, 702]
+    );
+    assert_eq!(reviews[0].title.as_deref(), Some("推荐一下"));
+    assert_eq!(reviews[0].content, "值得一看");
+    assert_eq!(reviews[0].author_name, "书评家");
+    assert_eq!(reviews[0].like_count, Some(8));
+    assert_eq!(reviews[0].reply_count, Some(1));
+    assert_eq!(reviews[1].title, None);
+    assert_eq!(reviews[1].author_avatar, None);
+    assert_eq!(client.novel_reviews(100041352, 1).await?, None);
+
+    let replies = client.review_replies(701, 0).await?.unwrap();
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0].content, "写得真好");
+    assert_eq!(client.review_replies(701, 1).await?, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn reading_progress() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let progress = client.reading_progress(100041352).await?.unwrap();
+    assert_eq!(progress.chapter_id, 9000002);
+    assert_eq!(
+        progress.read_time.map(|time| time.to_string()),
+        Some(String::from("2024-05-02 21:00:00"))
+    );
+    assert_eq!(client.reading_progress(1).await?, None);
+
+    client.set_reading_progress(100041352, 9000003).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn search() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let categories = client.categories().await?;
+    assert_eq!(
+        categories
+            .iter()
+            .map(|category| category.id.unwrap())
+            .collect::<Vec<_>>(),
+        vec![1, 5]
+    );
+    assert_eq!(categories[0].name, "灵异未知");
+
+    let tags = client.tags().await?;
+    assert!(tags.iter().any(|tag| tag.name == "恋爱"));
+
+    let options = Options {
+        keyword: Some(String::from("测试")),
+        excluded_tags: Some(vec![Tag {
+            id: None,
+            name: String::from("恋爱"),
+        }]),
+        ..Default::default()
+    };
+    assert_eq!(
+        client.search_infos(&options, 0, 12).await?,
+        Some(vec![100041352])
+    );
+    assert_eq!(client.search_infos(&options, 1, 12).await?, None);
+
+    let summaries = client.search_summaries(&options, 0).await?.unwrap();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].name, "测试小说");
+    assert_eq!(summaries[0].is_vip, Some(true));
+    assert_eq!(summaries[0].category.as_ref().unwrap().name, "仙侠武侠");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rankings() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let summaries = client
+        .rankings(RankingKind::Popular, RankingPeriod::Week, 0)
+        .await?
+        .unwrap();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].id, 100041353);
+    assert_eq!(summaries[0].is_finished, Some(true));
+    assert_eq!(summaries[0].category.as_ref().unwrap().name, "灵异未知");
+    assert_eq!(
+        client
+            .rankings(RankingKind::Popular, RankingPeriod::Week, 1)
+            .await?,
+        None
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn cassette() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("ciweimao.json");
+
+    let host = {
+        let server = MockServer::start(Router::new().fallback(handler)).await?;
+        let client = CiweimaoClient::builder()
+            .proxy(server.url().clone())
+            .host(server.url().clone())
+            .storage(Storage::root(dir.path()))
+            .cassette(Cassette::record(&path))
+            .build()
+            .await?;
+
+        assert_eq!(client.user_info().await?.nickname, "tester");
+        assert!(client.novel_info(100041352).await?.is_some());
+        assert!(client.volume_infos(100041352).await?.is_some());
+
+        server.url().clone()
+    };
+
+    assert!(std::fs::read_to_string(&path)?.contains(r#""reader_name": " tester ""#));
+
+    let client = CiweimaoClient::builder()
+        .no_proxy()
+        .host(host)
+        .storage(Storage::root(dir.path()))
+        .cassette(Cassette::replay(&path)?)
+        .build()
+        .await?;
+
+    assert_eq!(client.user_info().await?.nickname, "tester");
+    assert_eq!(
+        client.novel_info(100041352).await?.unwrap().name,
+        "测试小说"
+    );
+    assert!(client.volume_infos(100041352).await?.is_some());
+
+    assert!(matches!(
+        client.novel_info(100041352).await,
+        Err(Error::Cassette(_))
+    ));
+
+    Ok(())
+}
+
+async fn error_handler(uri: Uri) -> Response {
+    let name = match uri.path() {
+        "/reader/get_my_info" => "login_expired.json",
+        "/chapter/buy" => "insufficient_balance.json",
+        _ => "server_error.json",
+    };
+
+    let json = common::fixture(SOURCE, name);
+    common::json_response(aes_256_cbc_no_iv_base64_encrypt(AES_KEY, &json))
+}
+
+#[tokio::test]
+async fn errors() -> Result<()> {
+    let server = MockServer::start(Router::new().fallback(error_handler)).await?;
+    let dir = tempfile::tempdir()?;
+
+    let client = CiweimaoClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .storage(Storage::root(dir.path()))
+        .build()
+        .await?;
+
+    assert!(matches!(client.user_info().await, Err(Error::LoginExpired)));
+
+    let chapter_info = ChapterInfo {
+        id: 1,
+        ..Default::default()
+    };
+    assert!(matches!(
+        client.buy_chapter(&chapter_info).await,
+        Err(Error::InsufficientBalance(_))
+    ));
+
+    match client.money().await {
+        Err(Error::ServerError { code, msg }) => {
+            assert_eq!(code, "999999");
+            assert_eq!(msg, "服务器内部错误");
+        }
+        result => panic!("Unexpected result: {result:?}"),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn profiles() -> Result<()> {
+    let server = MockServer::start(Router::new().fallback(handler)).await?;
+
+    let dir = tempfile::tempdir()?;
+    let storage = Storage::root(dir.path());
+
+    let profile = Profile::new("profile-test-a")?;
+    {
+        let client = CiweimaoClient::builder()
+            .proxy(server.url().clone())
+            .host(server.url().clone())
+            .profile(profile.clone())
+            .storage(storage.clone())
+            .key_source(KeySource::passphrase("passphrase"))
+            .build()
+            .await?;
+
+        client
+            .log_in(String::from("tester"), Some(String::from("password")))
+            .await?;
+        client.shutdown().await?;
+    }
+
+    assert!(dir
+        .path()
+        .join("config/ciweimao/profiles/profile-test-a/config.toml")
+        .is_file());
+
+    let profiles = novel_api::profiles(Source::Ciweimao, &storage)?;
+    assert_eq!(profiles, vec!["default", "profile-test-a"]);
+
+    let client = CiweimaoClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .profile(profile.clone())
+        .storage(storage.clone())
+        .key_source(KeySource::passphrase("passphrase"))
+        .build()
+        .await?;
+    assert!(client.logged_in().await?);
+    drop(client);
+
+    // The key source belongs to the client, another passphrase can not open the config file
+    let client = CiweimaoClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .profile(profile)
+        .storage(storage.clone())
+        .key_source(KeySource::passphrase("another passphrase"))
+        .build()
+        .await?;
+    assert!(!client.logged_in().await?);
+    drop(client);
+
+    let client = CiweimaoClient::builder()
+        .profile(Profile::new("profile-test-b")?)
+        .storage(storage.clone())
+        .build()
+        .await?;
+    assert!(!client.logged_in().await?);
+    drop(client);
+
+    novel_api::delete_profile(Source::Ciweimao, "profile-test-a", &storage)?;
+    assert!(!novel_api::profiles(Source::Ciweimao, &storage)?
+        .iter()
+        .any(|name| name == "profile-test-a"));
+    assert!(matches!(
+        novel_api::delete_profile(Source::Ciweimao, "profile-test-a", &storage),
+        Err(Error::NotFound(_))
+    ));
+
+    assert!(matches!(
+        Profile::new("../default"),
+        Err(Error::InvalidOptions(_))
+    ));
+
+    Ok(())
+}
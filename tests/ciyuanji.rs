@@ -0,0 +1,604 @@
+mod common;
+
+use anyhow::Result;
+use axum::{
+    http::{Method, Uri},
+    response::Response,
+    Router,
+};
+use des::{
+    cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyInit},
+    Des,
+};
+use hex_simd::AsciiCase;
+use md5::{Digest, Md5};
+use novel_api::{
+    Cassette, Category, ChapterInfo, CiyuanjiClient, Client, ContentInfo, Error, Options,
+    PurchaseStatus, RankingKind, RankingPeriod, SortOrder, Storage, Tag,
+};
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use tempfile::TempDir;
+use url::form_urlencoded;
+
+use self::common::MockServer;
+
+const SOURCE: &str = "ciyuanji";
+
+const DES_KEY: &str = "ZUreQN0E";
+const KEY_PARAM: &str = "NpkTYvpvhJjEog8Y051gQDHmReY54z5t3F0zSd9QEFuxWGqfC8g8Y4GPuabq0KPdxArlji4dSnnHCARHnkqYBLu7iIw55ibTo18";
+
+fn des_ecb_base64_encrypt(data: &str) -> String {
+    type DesEcbEnc = ecb::Encryptor<Des>;
+    let encrypted =
+        DesEcbEnc::new(DES_KEY.as_bytes().into()).encrypt_padded_vec_mut::<Pkcs7>(data.as_bytes());
+
+    base64_simd::STANDARD.encode_to_string(encrypted)
+}
+
+fn des_ecb_base64_decrypt(data: &str) -> String {
+    let data = base64_simd::STANDARD.decode_to_vec(data).unwrap();
+
+    type DesEcbDec = ecb::Decryptor<Des>;
+    let decrypted = DesEcbDec::new(DES_KEY.as_bytes().into())
+        .decrypt_padded_vec_mut::<Pkcs7>(&data)
+        .unwrap();
+
+    String::from_utf8(decrypted).unwrap()
+}
+
+/// Check the signature of the `GenericRequest` envelope and return the decrypted `param`
+fn open_envelope(envelope: &Value) -> Value {
+    let field = |name: &str| match &envelope[name] {
+        Value::String(value) => value.clone(),
+        Value::Number(value) => value.to_string(),
+        other => panic!("Invalid envelope field `{name}`: {other}"),
+    };
+
+    let param = field("param");
+    let request_id = field("requestId");
+    let timestamp = field("timestamp");
+
+    let mut hasher = Md5::new();
+    hasher.update(base64_simd::STANDARD.encode_to_string(format!(
+        "param={param}&requestId={request_id}&timestamp={timestamp}&key={KEY_PARAM}"
+    )));
+    let sign = hex_simd::encode_to_string(hasher.finalize(), AsciiCase::Upper);
+    assert_eq!(field("sign"), sign);
+
+    let param: Value = serde_json::from_str(&des_ecb_base64_decrypt(&param)).unwrap();
+    assert_eq!(param["timestamp"].to_string(), timestamp);
+
+    param
+}
+
+async fn handler(method: Method, uri: Uri, body: String) -> Response {
+    let path = uri.path();
+
+    if path.starts_with("/images/") {
+        return common::image_response();
+    }
+
+    let envelope = if method == Method::GET {
+        Value::Object(
+            form_urlencoded::parse(uri.query().unwrap_or_default().as_bytes())
+                .into_owned()
+                .map(|(key, value)| (key, Value::String(value)))
+                .collect(),
+        )
+    } else {
+        serde_json::from_str(&body).unwrap()
+    };
+    let param = open_envelope(&envelope);
+
+    let name = match (method, path) {
+        (Method::GET, "/user/getUserInfo") => "getUserInfo.json",
+        (Method::GET, "/account/getAccountByUser") => "getAccountByUser.json",
+        (Method::POST, "/sign/sign") => "generic.json",
+        (Method::GET, "/bookrack/getUserBookRackList") => "getUserBookRackList.json",
+        (Method::POST, "/bookrack/addBookRack") => {
+            assert_eq!(param["bookId"], "10995");
+            "generic.json"
+        }
+        (Method::POST, "/bookrack/deleteBookRack") => {
+            assert_eq!(param["bookIds"], "10993");
+            "generic.json"
+        }
+        (Method::GET, "/book/getBookDetail") if param["bookId"] == "10993" => "getBookDetail.json",
+        (Method::GET, "/book/getBookDetail") => "book_not_found.json",
+        (Method::GET, "/author/getAuthorInfo") if param["authorId"] == "2001" => {
+            "getAuthorInfo.json"
+        }
+        (Method::GET, "/author/getAuthorInfo") => "empty_author.json",
+        (Method::GET, "/author/getAuthorBookList") => "getAuthorBookList.json",
+        (Method::GET, "/chapter/getChapterListByBookId") => "getChapterListByBookId.json",
+        (Method::GET, "/chapter/getChapterContent") => {
+            assert_eq!(param["bookId"], "10993");
+
+            let content = des_ecb_base64_encrypt("  第一段  \n第二段");
+            let json =
+                common::fixture(SOURCE, "getChapterContent.json").replace("{content}", &content);
+            return common::json_response(json);
+        }
+        (Method::POST, "/order/consume") => {
+            // The consecutive chapters are bought with one order
+            assert!(matches!(
+                (param["productId"].as_str(), param["buyCount"].as_str()),
+                (Some("9000002"), Some("1")) | (Some("9000004"), Some("2"))
+            ));
+            "generic.json"
+        }
+        (Method::GET, "/comment/getChapterCommentList") if param["pageNo"] == 1 => {
+            "getChapterCommentList.json"
+        }
+        (Method::GET, "/comment/getChapterCommentList") => "empty_comment_list.json",
+        (Method::GET, "/review/getBookReviewList") if param["pageNo"] == 1 => {
+            "getBookReviewList.json"
+        }
+        (Method::GET, "/review/getBookReviewList") => "empty_review_list.json",
+        (Method::GET, "/review/getReviewReplyList") if param["pageNo"] == 1 => {
+            "getChapterCommentList.json"
+        }
+        (Method::GET, "/review/getReviewReplyList") => "empty_comment_list.json",
+        (Method::GET, "/paragraph/getParagraphCommentCount") => "getParagraphCommentCount.json",
+        (Method::GET, "/paragraph/getParagraphCommentList") => {
+            assert_eq!(param["paragraphIndex"], 1);
+
+            if param["pageNo"] == 1 {
+                "getChapterCommentList.json"
+            } else {
+                "empty_comment_list.json"
+            }
+        }
+        (Method::GET, "/bookrack/getReadRecord") if param["bookId"] == "10993" => {
+            "getReadRecord.json"
+        }
+        (Method::GET, "/bookrack/getReadRecord") => "empty_read_record.json",
+        (Method::POST, "/bookrack/saveReadRecord") => {
+            assert_eq!(param["chapterId"], "9000003");
+            "generic.json"
+        }
+        (Method::GET, "/classify/getBookClassifyListByParams") => {
+            "getBookClassifyListByParams.json"
+        }
+        (Method::GET, "/tag/getAppTagList") => "getAppTagList.json",
+        (Method::GET, "/book/searchBookList") if param["pageNo"] == 1 => "searchBookList.json",
+        (Method::GET, "/book/searchBookList") => "empty_search.json",
+        (Method::GET, "/book/getBookListByParams") if param["pageNo"] == 1 => {
+            "getBookListByParams.json"
+        }
+        (Method::GET, "/book/getBookListByParams") => "empty_book_list.json",
+        (Method::GET, "/rank/getRankBookList") if param["pageNo"] == 1 => "getRankBookList.json",
+        (Method::GET, "/rank/getRankBookList") => "empty_book_list.json",
+        _ => return common::not_found(),
+    };
+
+    common::json_response(common::fixture(SOURCE, name))
+}
+
+async fn client() -> Result<(MockServer, TempDir, CiyuanjiClient)> {
+    let server = MockServer::start(Router::new().fallback(handler)).await?;
+    let dir = tempfile::tempdir()?;
+
+    let client = CiyuanjiClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .storage(Storage::root(dir.path()))
+        .build()
+        .await?;
+
+    Ok((server, dir, client))
+}
+
+#[tokio::test]
+async fn user() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let user_info = client.user_info().await?;
+    assert_eq!(user_info.nickname, "tester");
+    assert_eq!(
+        user_info.avatar,
+        Some(common::image_url("/images/avatar.png"))
+    );
+
+    assert_eq!(client.money().await?, 120);
+    client.sign_in().await?;
+    assert_eq!(client.bookshelf_infos().await?, vec![10993, 10994]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn bookshelf() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let bookshelves = client.bookshelves().await?;
+    assert_eq!(bookshelves.len(), 1);
+    assert_eq!(bookshelves[0].novel_ids, vec![10993, 10994]);
+
+    let shelf_id = bookshelves[0].id;
+    client.add_to_bookshelf(10995, shelf_id).await?;
+    client.remove_from_bookshelf(10993, shelf_id).await?;
+    assert!(matches!(
+        client.add_to_bookshelf(10995, shelf_id + 1).await,
+        Err(Error::NotFound(_))
+    ));
+    assert!(matches!(
+        client.move_between_shelves(10993, shelf_id, shelf_id).await,
+        Err(Error::Unsupported(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn novel() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let novel_info = client.novel_info(10993).await?.unwrap();
+    assert_eq!(novel_info.name, "测试小说");
+    assert_eq!(novel_info.author_id, Some(2001));
+    assert_eq!(novel_info.author_name, "测试作者");
+    assert_eq!(
+        novel_info.introduction,
+        Some(vec![String::from("第一行简介"), String::from("第二行简介")])
+    );
+    assert_eq!(novel_info.word_count, Some(123456));
+    assert_eq!(novel_info.is_vip, Some(true));
+    assert_eq!(novel_info.is_finished, Some(false));
+    assert_eq!(
+        novel_info.category,
+        Some(Category {
+            id: Some(12),
+            parent_id: Some(1),
+            name: String::from("幻想-异世界"),
+        })
+    );
+    assert_eq!(
+        novel_info.tags,
+        Some(vec![Tag {
+            id: Some(30),
+            name: String::from("百合"),
+        }])
+    );
+
+    assert!(client.novel_info(1).await?.is_none());
+
+    let volume_infos = client.volume_infos(10993).await?.unwrap();
+    assert_eq!(
+        volume_infos
+            .iter()
+            .map(|volume_info| volume_info.title.as_str())
+            .collect::<Vec<_>>(),
+        vec!["第一卷", "第二卷"]
+    );
+
+    let chapter_infos = &volume_infos[0].chapter_infos;
+    assert_eq!(chapter_infos.len(), 2);
+    assert!(chapter_infos[0].can_download());
+    assert!(!chapter_infos[1].can_download());
+    assert_eq!(chapter_infos[1].price, Some(32));
+    assert!(volume_infos[1].chapter_infos[0].can_download());
+
+    let content_infos = client.content_infos(&chapter_infos[0]).await?;
+    assert_eq!(content_infos.len(), 3);
+    assert!(matches!(&content_infos[0], ContentInfo::Text(text) if text == "第一段"));
+    assert!(matches!(&content_infos[1], ContentInfo::Image(url) if url.path() == "/images/1.png"));
+    assert!(matches!(&content_infos[2], ContentInfo::Text(text) if text == "第二段"));
+
+    client.buy_chapter(&chapter_infos[1]).await?;
+
+    let image = client.image(&common::image_url("/images/1.png")).await?;
+    assert_eq!(image.width(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn purchase() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let purchases = client.buy_all_remaining(10993).await?;
+    assert_eq!(
+        purchases
+            .iter()
+            .map(|purchase| (purchase.chapter_id, &purchase.status))
+            .collect::<Vec<_>>(),
+        vec![
+            (9000001, &PurchaseStatus::Skipped),
+            (9000002, &PurchaseStatus::Bought),
+            (9000003, &PurchaseStatus::Skipped),
+            (9000004, &PurchaseStatus::Bought),
+            (9000005, &PurchaseStatus::Bought)
+        ]
+    );
+
+    // The balance is checked before anything is bought
+    let chapter_info = ChapterInfo {
+        novel_id: Some(10993),
+        id: 9000002,
+        price: Some(200),
+        ..Default::default()
+    };
+    assert!(matches!(
+        client.buy_chapters(&[chapter_info]).await,
+        Err(Error::InsufficientBalance(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn comments() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let volume_infos = client.volume_infos(10993).await?.unwrap();
+    let chapter_info = &volume_infos[0].chapter_infos[0];
+
+    let comments = client.chapter_comments(chapter_info, 0).await?.unwrap();
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].id, 501);
+    assert_eq!(comments[0].author_name, "读者");
+    assert_eq!(comments[0].content, "写得真好");
+    assert_eq!(comments[0].like_count, Some(12));
+    assert_eq!(comments[0].reply_count, Some(3));
+    assert_eq!(client.chapter_comments(chapter_info, 1).await?, None);
+
+    // The second text paragraph follows an image
+    let counts = client.paragraph_comment_counts(chapter_info).await?;
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts[&0], 3);
+    assert_eq!(counts[&2], 5);
+
+    let comments = client
+        .paragraph_comments(chapter_info, 2, 0)
+        .await?
+        .unwrap();
+    assert_eq!(comments[0].id, 501);
+    assert_eq!(comments[0].author_avatar.is_some(), true);
+    assert_eq!(client.paragraph_comments(chapter_info, 2, 1).await?, None);
+    assert!(matches!(
+        client.paragraph_comments(chapter_info, 1, 0).await,
+        Err(Error::NotFound(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn author() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let author = client.author_info(2001).await?.unwrap();
+    assert_eq!(author.name, "测试作者");
+    assert!(author.avatar.is_some());
+    assert_eq!(
+        author.introduction,
+        Some(vec![String::from("第一行介绍"), String::from("第二行介绍")])
+    );
+    assert_eq!(author.follower_count, Some(321));
+    assert_eq!(client.author_info(1).await?, None);
+
+    let novels = client.author_novels(2001).await?;
+    assert_eq!(novels.len(), 1);
+    assert_eq!(novels[0].id, 10993);
+    assert_eq!(novels[0].author_name.as_deref(), Some("测试作者"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn reviews() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let reviews = client.novel_reviews(10993, 0).await?.unwrap();
+    assert_eq!(
+        reviews.iter().map(|review| review.id).collect::<Vec<_>>(),
+        vec![701, 702]
+    );
+    assert_eq!(reviews[0].title.as_deref(), Some("推荐一下"));
+    assert_eq!(reviews[0].content, "值得一看");
+    assert_eq!(reviews[0].author_name, "书评家");
+    assert_eq!(reviews[0].like_count, Some(8));
+    assert_eq!(reviews[0].reply_count, Some(1));
+    assert_eq!(reviews[1].title, None);
+    assert_eq!(reviews[1].author_avatar, None);
+    assert_eq!(client.novel_reviews(10993, 1).await?, None);
+
+    let replies = client.review_replies(701, 0).await?.unwrap();
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0].content, "写得真好");
+    assert_eq!(client.review_replies(701, 1).await?, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn reading_progress() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let progress = client.reading_progress(10993).await?.unwrap();
+    assert_eq!(progress.chapter_id, 9000002);
+    assert_eq!(
+        progress.read_time.map(|time| time.to_string()),
+        Some(String::from("2024-05-02 21:00:00"))
+    );
+    assert_eq!(client.reading_progress(1).await?, None);
+
+    client.set_reading_progress(10993, 9000003).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn search() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let categories = client.categories().await?;
+    assert_eq!(
+        categories
+            .iter()
+            .map(|category| category.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["幻想", "幻想-异世界"]
+    );
+
+    let tags = client.tags().await?;
+    assert!(tags.iter().any(|tag| tag.name == "恋爱"));
+
+    let options = Options {
+        tags: Some(vec![Tag {
+            id: Some(30),
+            name: String::from("百合"),
+        }]),
+        ..Default::default()
+    };
+    assert_eq!(
+        client.search_infos(&options, 0, 12).await?,
+        Some(vec![10993])
+    );
+    assert_eq!(client.search_infos(&options, 1, 12).await?, None);
+
+    let options = Options {
+        keyword: Some(String::from("测试")),
+        excluded_tags: Some(vec![Tag {
+            id: Some(3),
+            name: String::from("恋爱"),
+        }]),
+        ..Default::default()
+    };
+    assert_eq!(
+        client.search_infos(&options, 0, 12).await?,
+        Some(vec![10995])
+    );
+    assert_eq!(client.search_infos(&options, 1, 12).await?, None);
+
+    let summaries = client.search_summaries(&options, 0).await?.unwrap();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].name, "另一本小说");
+    assert_eq!(summaries[0].word_count, None);
+    assert_eq!(summaries[0].is_finished, Some(true));
+    assert!(summaries[0].category.is_none());
+
+    let options = Options {
+        sort: Some(SortOrder::Bookmarks),
+        ..Default::default()
+    };
+    assert!(matches!(
+        client.search_infos(&options, 0, 12).await,
+        Err(Error::Unsupported(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rankings() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let summaries = client
+        .rankings(RankingKind::NewBooks, RankingPeriod::Total, 0)
+        .await?
+        .unwrap();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].id, 10994);
+    assert_eq!(summaries[0].name, "第二本小说");
+    assert_eq!(summaries[0].is_vip, Some(false));
+    assert_eq!(
+        client
+            .rankings(RankingKind::NewBooks, RankingPeriod::Total, 1)
+            .await?,
+        None
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn cassette() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("ciyuanji.json");
+
+    let host = {
+        let server = MockServer::start(Router::new().fallback(handler)).await?;
+        let client = CiyuanjiClient::builder()
+            .proxy(server.url().clone())
+            .host(server.url().clone())
+            .storage(Storage::root(dir.path()))
+            .cassette(Cassette::record(&path))
+            .build()
+            .await?;
+
+        assert_eq!(client.user_info().await?.nickname, "tester");
+        assert!(client.novel_info(10993).await?.is_some());
+        assert!(client.volume_infos(10993).await?.is_some());
+
+        server.url().clone()
+    };
+
+    assert!(std::fs::read_to_string(&path)?.contains(r#""param": "{\"bookId\":\"10993\"}""#));
+
+    let client = CiyuanjiClient::builder()
+        .no_proxy()
+        .host(host)
+        .storage(Storage::root(dir.path()))
+        .cassette(Cassette::replay(&path)?)
+        .build()
+        .await?;
+
+    assert_eq!(client.user_info().await?.nickname, "tester");
+    assert_eq!(client.novel_info(10993).await?.unwrap().name, "测试小说");
+    assert!(client.volume_infos(10993).await?.is_some());
+
+    assert!(matches!(
+        client.novel_info(10993).await,
+        Err(Error::Cassette(_))
+    ));
+
+    Ok(())
+}
+
+async fn error_handler(uri: Uri) -> Response {
+    let name = match uri.path() {
+        "/user/getUserInfo" => "login_expired.json",
+        "/chapter/getChapterContent" => "chapter_locked.json",
+        _ => "server_error.json",
+    };
+
+    common::json_response(common::fixture(SOURCE, name))
+}
+
+#[tokio::test]
+async fn errors() -> Result<()> {
+    let server = MockServer::start(Router::new().fallback(error_handler)).await?;
+    let dir = tempfile::tempdir()?;
+
+    let client = CiyuanjiClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .storage(Storage::root(dir.path()))
+        .build()
+        .await?;
+
+    assert!(matches!(client.user_info().await, Err(Error::LoginExpired)));
+
+    let chapter_info = ChapterInfo {
+        novel_id: Some(10993),
+        id: 1,
+        ..Default::default()
+    };
+    assert!(matches!(
+        client.content_infos(&chapter_info).await,
+        Err(Error::ChapterLocked(_))
+    ));
+
+    match client.money().await {
+        Err(Error::ServerError { code, msg }) => {
+            assert_eq!(code, "500");
+            assert_eq!(msg, "系统异常");
+        }
+        result => panic!("Unexpected result: {result:?}"),
+    }
+
+    Ok(())
+}
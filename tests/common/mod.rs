@@ -0,0 +1,107 @@
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    io::Cursor,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Router,
+};
+use image::{DynamicImage, ImageFormat};
+use tokio::{net::TcpListener, sync::oneshot, task};
+use url::Url;
+
+/// Images in the fixtures live on this host, the clients reach it by using the server as a proxy
+pub const IMAGE_HOST: &str = "http://images.test";
+
+/// A local stand-in for the remote Apis, stopped when dropped
+pub struct MockServer {
+    url: Url,
+    stop_tx: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Start a server on a free port, it also serves as an HTTP proxy
+    pub async fn start(app: Router) -> Result<Self> {
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+        let listener = TcpListener::bind(addr).await?;
+
+        let url = Url::parse(&format!("http://{}", listener.local_addr()?))?;
+
+        let (stop_tx, stop_rx) = oneshot::channel();
+
+        task::spawn(async move {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    stop_rx.await.ok();
+                })
+                .await
+        });
+
+        Ok(Self {
+            url,
+            stop_tx: Some(stop_tx),
+        })
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(stop_tx) = self.stop_tx.take() {
+            stop_tx.send(()).ok();
+        }
+    }
+}
+
+/// Read `tests/fixtures/{source}/{name}`, `{image_host}` is replaced with `IMAGE_HOST`
+pub fn fixture(source: &str, name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(source)
+        .join(name);
+
+    fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("Failed to read fixture `{}`: {err}", path.display()))
+        .replace("{image_host}", IMAGE_HOST)
+}
+
+pub fn json_response<T>(body: T) -> Response
+where
+    T: Into<String>,
+{
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/json")],
+        body.into(),
+    )
+        .into_response()
+}
+
+pub fn image_response() -> Response {
+    let mut bytes = Vec::new();
+    DynamicImage::new_rgb8(2, 2)
+        .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+        .unwrap();
+
+    (StatusCode::OK, [(header::CONTENT_TYPE, "image/png")], bytes).into_response()
+}
+
+pub fn not_found() -> Response {
+    (StatusCode::NOT_FOUND, "404 Not Found").into_response()
+}
+
+/// Url of an image in the fixtures
+pub fn image_url(path: &str) -> Url {
+    Url::parse(IMAGE_HOST).unwrap().join(path).unwrap()
+}
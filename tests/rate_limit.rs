@@ -0,0 +1,85 @@
+mod common;
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use axum::Router;
+use novel_api::{Client, ClientBuilder, RateLimit, SfacgClient, Storage};
+use tempfile::TempDir;
+use tokio::task::JoinSet;
+
+use self::common::MockServer;
+
+const SOURCE: &str = "sfacg";
+
+async fn client(
+    configure: impl FnOnce(ClientBuilder<SfacgClient>) -> ClientBuilder<SfacgClient>,
+) -> Result<(MockServer, TempDir, SfacgClient)> {
+    let app = Router::new()
+        .fallback(|| async { common::json_response(common::fixture(SOURCE, "money.json")) });
+    let server = MockServer::start(app).await?;
+    let dir = tempfile::tempdir()?;
+
+    let builder = SfacgClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .storage(Storage::root(dir.path()));
+    let client = configure(builder).build().await?;
+
+    Ok((server, dir, client))
+}
+
+/// Send `count` requests at the same time from different tasks and return the elapsed time
+async fn concurrent_requests(client: SfacgClient, count: usize) -> Result<Duration> {
+    let client = Arc::new(client);
+    let now = Instant::now();
+
+    let mut tasks = JoinSet::new();
+    for _ in 0..count {
+        let client = Arc::clone(&client);
+        tasks.spawn(async move { client.money().await });
+    }
+    while let Some(result) = tasks.join_next().await {
+        result??;
+    }
+
+    Ok(now.elapsed())
+}
+
+#[tokio::test]
+async fn shared_by_tasks() -> Result<()> {
+    let (_server, _dir, client) =
+        client(|builder| builder.rate_limit(RateLimit::per_second(20))).await?;
+
+    // The first request is sent immediately, the other four wait 50ms each
+    assert!(concurrent_requests(client, 5).await? >= Duration::from_millis(200));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn burst() -> Result<()> {
+    let (_server, _dir, client) = client(|builder| {
+        builder.rate_limit(RateLimit::min_interval(Duration::from_secs(1)).burst(5))
+    })
+    .await?;
+
+    assert!(concurrent_requests(client, 5).await? < Duration::from_secs(1));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn image_host_is_limited_separately() -> Result<()> {
+    let (_server, _dir, client) = client(|builder| {
+        builder.image_rate_limit(RateLimit::min_interval(Duration::from_secs(10)))
+    })
+    .await?;
+
+    assert!(concurrent_requests(client, 5).await? < Duration::from_secs(1));
+
+    Ok(())
+}
@@ -0,0 +1,181 @@
+mod common;
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Router,
+};
+use novel_api::{Client, Error, RetryPolicy, SfacgClient, Storage};
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+use self::common::MockServer;
+
+const SOURCE: &str = "sfacg";
+
+const BUSY: &str =
+    r#"{ "status": { "httpCode": 503, "errorCode": 503, "msg": "服务器繁忙" }, "data": null }"#;
+const TOO_MANY_REQUESTS: &str =
+    r#"{ "status": { "httpCode": 429, "errorCode": 429, "msg": "请求过于频繁" }, "data": null }"#;
+
+/// A server that answers `/user/money` with `failure` for the first `failures` requests
+async fn flaky_server(
+    failures: usize,
+    failure: fn() -> Response,
+) -> Result<(MockServer, Arc<AtomicUsize>)> {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&hits);
+
+    let app = Router::new().fallback(move || {
+        let counter = Arc::clone(&counter);
+        async move {
+            if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                failure()
+            } else {
+                common::json_response(common::fixture(SOURCE, "money.json"))
+            }
+        }
+    });
+
+    Ok((MockServer::start(app).await?, hits))
+}
+
+fn service_unavailable() -> Response {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::CONTENT_TYPE, "application/json")],
+        BUSY,
+    )
+        .into_response()
+}
+
+fn too_many_requests(retry_after: &'static str) -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [
+            (header::CONTENT_TYPE, "application/json"),
+            (header::RETRY_AFTER, retry_after),
+        ],
+        TOO_MANY_REQUESTS,
+    )
+        .into_response()
+}
+
+fn fast_policy() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_secs(2),
+        jitter: false,
+        ..Default::default()
+    }
+}
+
+async fn client(server: &MockServer, retry_policy: RetryPolicy) -> Result<(TempDir, SfacgClient)> {
+    let dir = tempfile::tempdir()?;
+
+    let client = SfacgClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .storage(Storage::root(dir.path()))
+        .retry_policy(retry_policy)
+        .build()
+        .await?;
+
+    Ok((dir, client))
+}
+
+#[tokio::test]
+async fn retry_status_codes() -> Result<()> {
+    let (server, hits) = flaky_server(2, service_unavailable).await?;
+    let (_dir, client) = client(&server, fast_policy()).await?;
+
+    assert_eq!(client.money().await?, 120);
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn give_up_after_max_attempts() -> Result<()> {
+    let (server, hits) = flaky_server(usize::MAX, service_unavailable).await?;
+    let (_dir, client) = client(&server, fast_policy()).await?;
+
+    assert!(matches!(
+        client.money().await,
+        Err(Error::ServerError { .. })
+    ));
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn no_retry() -> Result<()> {
+    let (server, hits) = flaky_server(1, service_unavailable).await?;
+    let (_dir, client) = client(&server, RetryPolicy::none()).await?;
+
+    assert!(client.money().await.is_err());
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn retry_after() -> Result<()> {
+    let (server, hits) = flaky_server(1, || too_many_requests("1")).await?;
+    let (_dir, client) = client(&server, fast_policy()).await?;
+
+    let now = Instant::now();
+    assert_eq!(client.money().await?, 120);
+    assert!(now.elapsed() >= Duration::from_secs(1));
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn retry_after_too_long() -> Result<()> {
+    let (server, hits) = flaky_server(1, || too_many_requests("3600")).await?;
+    let (_dir, client) = client(&server, fast_policy()).await?;
+
+    // The response is returned when the server asks to wait longer than the maximum backoff
+    assert!(matches!(client.money().await, Err(Error::RateLimited(_))));
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn retry_connect_errors() -> Result<()> {
+    let server = MockServer::start(Router::new()).await?;
+    let url = server.url().clone();
+    drop(server);
+
+    let dir = tempfile::tempdir()?;
+    let client = SfacgClient::builder()
+        .proxy(url.clone())
+        .host(url)
+        .storage(Storage::root(dir.path()))
+        .retry_policy(fast_policy())
+        .build()
+        .await?;
+
+    let now = Instant::now();
+    assert!(matches!(
+        client.money().await,
+        Err(Error::Reqwest(err)) if err.is_connect()
+    ));
+    assert!(now.elapsed() >= Duration::from_millis(30));
+
+    Ok(())
+}
@@ -1,35 +1,922 @@
+mod common;
+
+use std::{fs, io::Read, sync::Arc, time::Duration};
+
 use anyhow::Result;
-use novel_api::{Client, Options, SfacgClient, WordCountRange};
+use axum::{
+    http::{Method, Uri},
+    response::Response,
+    Router,
+};
+use futures_util::TryStreamExt;
+use novel_api::{
+    Cassette, ChapterInfo, ChapterPurchase, Client, ContentInfo, DownloadEvent, DownloadOptions,
+    Error, ExportOptions, Freshness, ImageMode, Options, PurchaseStatus, RankingKind,
+    RankingPeriod, RetryPolicy, SfacgClient, SortOrder, SpendingPolicy, Storage, WordCountRange,
+};
+use pretty_assertions::assert_eq;
+use sea_orm::ConnectionTrait;
+use tempfile::TempDir;
+use url::{form_urlencoded, Url};
+
+use self::common::MockServer;
+
+const SOURCE: &str = "sfacg";
+
+async fn handler(method: Method, uri: Uri) -> Response {
+    let path = uri.path();
+
+    if path.starts_with("/images/") {
+        return common::image_response();
+    }
+
+    let query: Vec<(String, String)> =
+        form_urlencoded::parse(uri.query().unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect();
+    let param = |name: &str| {
+        query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let page = param("page");
+
+    let name = match (method, path) {
+        (Method::POST, "/sessions") => "generic.json",
+        (Method::GET, "/user") => "user.json",
+        (Method::GET, "/user/money") => "money.json",
+        (Method::PUT, "/user/newSignInfo") => "generic.json",
+        (Method::GET, "/user/Pockets") => "pockets.json",
+        (Method::POST, "/pockets/2/novels") => "generic.json",
+        (Method::DELETE, "/pockets/1/novels/263060") => "generic.json",
+        (Method::GET, "/user/readrecords/263060") => "readrecords.json",
+        (Method::GET, "/user/readrecords/1") => "not_found.json",
+        (Method::PUT, "/user/readrecords/263060") => "generic.json",
+        (Method::GET, "/novels/263060") => "novel.json",
+        (Method::GET, "/novels/263060/Cmts") if page == Some("0") => "novel_cmts.json",
+        (Method::GET, "/novels/263060/Cmts") => "empty_cmts.json",
+        (Method::GET, "/cmts/701/replys") if page == Some("0") => "cmts.json",
+        (Method::GET, "/cmts/701/replys") => "empty_cmts.json",
+        (Method::GET, "/novels/263060/dirs") => "dirs.json",
+        (Method::GET, "/authors/1001") => "author.json",
+        (Method::GET, "/authors/1001/novels") => "author_novels.json",
+        (Method::GET, "/authors/1") => "not_found.json",
+        (Method::POST, "/novels/263060/orderedchaps") => "created.json",
+        (Method::GET, "/Chaps/9000001") => "chap.json",
+        (Method::GET, "/Chaps/9000001/Cmts") if page == Some("0") => "cmts.json",
+        (Method::GET, "/Chaps/9000001/Cmts") => "empty_cmts.json",
+        (Method::GET, "/Chaps/9000001/paragraphCmts/count") => "paragraph_cmts_count.json",
+        (Method::GET, "/Chaps/9000001/paragraphCmts") => {
+            assert_eq!(param("paragraphIndex"), Some("1"));
+
+            if page == Some("0") {
+                "cmts.json"
+            } else {
+                "empty_cmts.json"
+            }
+        }
+        (Method::GET, "/noveltypes") => "noveltypes.json",
+        (Method::GET, "/novels/0/sysTags") => "systags.json",
+        (Method::GET, "/search/novels/result/new") if page == Some("0") => "search.json",
+        (Method::GET, "/search/novels/result/new") => "empty_search.json",
+        (Method::GET, "/novels/0/sysTags/novels") if page == Some("0") => "novels.json",
+        (Method::GET, "/novels/0/sysTags/novels") => "empty_novels.json",
+        (Method::GET, "/ranks/month/novels") if page == Some("0") => "ranks.json",
+        (Method::GET, "/ranks/month/novels") => "empty_novels.json",
+        (Method::GET, _) if path.starts_with("/novels/") => "not_found.json",
+        _ => return common::not_found(),
+    };
+
+    common::json_response(common::fixture(SOURCE, name))
+}
+
+async fn client() -> Result<(MockServer, TempDir, SfacgClient)> {
+    let server = MockServer::start(Router::new().fallback(handler)).await?;
+    let dir = tempfile::tempdir()?;
+
+    let client = SfacgClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .storage(Storage::root(dir.path()))
+        .build()
+        .await?;
+
+    Ok((server, dir, client))
+}
 
 #[tokio::test]
-async fn main() -> Result<()> {
-    let client = SfacgClient::new().await?;
+async fn user() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    client
+        .log_in(String::from("username"), Some(String::from("password")))
+        .await?;
+    assert!(client.logged_in().await?);
 
-    let novel_id = 263060;
+    let user_info = client.user_info().await?;
+    assert_eq!(user_info.nickname, "tester");
+    assert_eq!(
+        user_info.avatar,
+        Some(common::image_url("/images/avatar.png"))
+    );
 
-    let novel_info = client.novel_info(novel_id).await?.unwrap();
-    println!("{novel_info:#?}");
+    assert_eq!(client.money().await?, 120);
+    client.sign_in().await?;
+    assert_eq!(client.bookshelf_infos().await?, vec![263060, 263061]);
 
-    let volume_infos = client.volume_infos(novel_id).await?.unwrap();
-    println!("{volume_infos:#?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn bookshelf() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let bookshelves = client.bookshelves().await?;
+    assert_eq!(bookshelves.len(), 2);
+    assert_eq!(bookshelves[0].id, 1);
+    assert_eq!(bookshelves[0].name, "默认书架");
+    assert_eq!(bookshelves[0].novel_ids, vec![263060, 263061]);
+    assert!(bookshelves[1].novel_ids.is_empty());
+
+    client.add_to_bookshelf(263060, 2).await?;
+    client.remove_from_bookshelf(263060, 1).await?;
+    client.move_between_shelves(263060, 1, 2).await?;
+    assert!(client.remove_from_bookshelf(263060, 3).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn novel() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let novel_info = client.novel_info(263060).await?.unwrap();
+    assert_eq!(novel_info.name, "测试小说");
+    assert_eq!(novel_info.author_id, Some(1001));
+    assert_eq!(novel_info.author_name, "测试作者");
+    assert_eq!(
+        novel_info.introduction,
+        Some(vec![String::from("第一行简介"), String::from("第二行简介")])
+    );
+    assert_eq!(novel_info.word_count, Some(123456));
+    assert_eq!(novel_info.is_vip, Some(true));
+    assert_eq!(novel_info.is_finished, Some(false));
+    assert_eq!(novel_info.category.unwrap().name, "魔幻");
+    // Non-system tags are ignored
+    assert_eq!(
+        novel_info
+            .tags
+            .unwrap()
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect::<Vec<_>>(),
+        vec![String::from("百合")]
+    );
+
+    assert!(client.novel_info(1).await?.is_none());
+
+    let volume_infos = client.volume_infos(263060).await?.unwrap();
+    assert_eq!(volume_infos.len(), 1);
+    assert_eq!(volume_infos[0].title, "第一卷");
+
+    let chapter_infos = &volume_infos[0].chapter_infos;
+    assert_eq!(chapter_infos.len(), 2);
+    assert!(chapter_infos[0].can_download());
+    assert!(!chapter_infos[1].can_download());
+    assert_eq!(chapter_infos[1].price, Some(32));
+
+    let content_infos = client.content_infos(&chapter_infos[0]).await?;
+    assert_eq!(content_infos.len(), 3);
+    assert!(matches!(&content_infos[0], ContentInfo::Text(text) if text == "第一段"));
+    assert!(matches!(&content_infos[1], ContentInfo::Image(url) if url.path() == "/images/1.png"));
+    assert!(matches!(&content_infos[2], ContentInfo::Text(text) if text == "第二段"));
+
+    client.buy_chapter(&chapter_infos[1]).await?;
+
+    let image = client.image(&common::image_url("/images/1.png")).await?;
+    assert_eq!(image.width(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn purchase() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let purchases = client.buy_all_remaining(263060).await?;
+    assert_eq!(
+        purchases
+            .iter()
+            .map(|purchase| (purchase.chapter_id, &purchase.status))
+            .collect::<Vec<_>>(),
+        vec![
+            (9000001, &PurchaseStatus::Skipped),
+            (9000002, &PurchaseStatus::Bought)
+        ]
+    );
+
+    // The balance is checked before anything is bought
+    let chapter_info = ChapterInfo {
+        novel_id: Some(263060),
+        id: 9000002,
+        price: Some(200),
+        ..Default::default()
+    };
+    assert!(matches!(
+        client.buy_chapters(&[chapter_info]).await,
+        Err(Error::InsufficientBalance(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn spending_policy() -> Result<()> {
+    let server = MockServer::start(Router::new().fallback(handler)).await?;
+    let dir = tempfile::tempdir()?;
+    let storage = Storage::root(dir.path());
+
+    let client = SfacgClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .storage(storage.clone())
+        .spending_policy(SpendingPolicy::dry_run())
+        .build()
+        .await?;
+
+    let purchases = client.buy_all_remaining(263060).await?;
+    assert_eq!(
+        purchases[1],
+        ChapterPurchase {
+            chapter_id: 9000002,
+            price: Some(32),
+            status: PurchaseStatus::DryRun
+        }
+    );
+    assert!(client.purchase_records().await?.is_empty());
+
+    let client = SfacgClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .storage(storage.clone())
+        .spending_policy(SpendingPolicy::default().novel_budget(40))
+        .build()
+        .await?;
+
+    let purchases = client.buy_all_remaining(263060).await?;
+    assert_eq!(purchases[1].status, PurchaseStatus::Bought);
+    assert_eq!(
+        client
+            .purchase_records()
+            .await?
+            .into_iter()
+            .map(|record| (record.novel_id, record.chapter_id, record.price))
+            .collect::<Vec<_>>(),
+        vec![(Some(263060), 9000002, Some(32))]
+    );
+
+    // The purchases saved in the audit log count towards the budget of the novel
+    let chapter_info = ChapterInfo {
+        novel_id: Some(263060),
+        id: 9000003,
+        price: Some(32),
+        ..Default::default()
+    };
+    assert!(matches!(
+        client.buy_chapter(&chapter_info).await,
+        Err(Error::BudgetExceeded(_))
+    ));
 
-    let content_infos = client
-        .content_infos(&volume_infos[0].chapter_infos[0])
+    let client = SfacgClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .storage(storage)
+        .spending_policy(SpendingPolicy::default().session_budget(40))
+        .build()
         .await?;
-    println!("{content_infos:#?}");
+
+    // The budget is reserved, so concurrent purchases can not exceed it together
+    let results = tokio::join!(
+        client.buy_chapter(&chapter_info),
+        client.buy_chapter(&chapter_info)
+    );
+    assert!(matches!(
+        results,
+        (Ok(()), Err(Error::BudgetExceeded(_))) | (Err(Error::BudgetExceeded(_)), Ok(()))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn purchase_audit_failure() -> Result<()> {
+    let (_server, dir, client) = client().await?;
+    assert!(client.purchase_records().await?.is_empty());
+
+    // Every insert into the audit log fails
+    let db_path = dir.path().join("data").join(SOURCE).join("novel.db");
+    let db = sea_orm::Database::connect(format!("sqlite:{}", db_path.display())).await?;
+    db.execute_unprepared(
+        "CREATE TRIGGER fail_purchase BEFORE INSERT ON purchase BEGIN SELECT RAISE(ABORT, 'failure'); END",
+    )
+    .await?;
+    db.close().await?;
+
+    // The order succeeded, so the chapter is still reported as bought
+    let purchases = client.buy_all_remaining(263060).await?;
+    assert_eq!(purchases[1].chapter_id, 9000002);
+    assert_eq!(purchases[1].status, PurchaseStatus::Bought);
+    assert!(client.purchase_records().await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn comments() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let volume_infos = client.volume_infos(263060).await?.unwrap();
+    let chapter_info = &volume_infos[0].chapter_infos[0];
+
+    let comments = client.chapter_comments(chapter_info, 0).await?.unwrap();
+    assert_eq!(comments.len(), 1);
+    assert_eq!(comments[0].id, 501);
+    assert_eq!(comments[0].author_name, "读者");
+    assert_eq!(comments[0].content, "写得真好");
+    assert_eq!(comments[0].like_count, Some(12));
+    assert_eq!(comments[0].reply_count, Some(3));
+    assert_eq!(client.chapter_comments(chapter_info, 1).await?, None);
+
+    // The second text paragraph follows an image
+    let counts = client.paragraph_comment_counts(chapter_info).await?;
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts[&0], 3);
+    assert_eq!(counts[&2], 5);
+
+    let comments = client
+        .paragraph_comments(chapter_info, 2, 0)
+        .await?
+        .unwrap();
+    assert_eq!(comments[0].id, 501);
+    assert_eq!(comments[0].author_avatar.is_some(), true);
+    assert_eq!(client.paragraph_comments(chapter_info, 2, 1).await?, None);
+    assert!(matches!(
+        client.paragraph_comments(chapter_info, 1, 0).await,
+        Err(Error::NotFound(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn reviews() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let reviews = client.novel_reviews(263060, 0).await?.unwrap();
+    assert_eq!(
+        reviews.iter().map(|review| review.id).collect::<Vec<_>>(),
+        vec![701, 702]
+    );
+    assert_eq!(reviews[0].title.as_deref(), Some("推荐一下"));
+    assert_eq!(reviews[0].content, "值得一看");
+    assert_eq!(reviews[0].author_name, "书评家");
+    assert_eq!(reviews[0].like_count, Some(8));
+    assert_eq!(reviews[0].reply_count, Some(1));
+    assert_eq!(reviews[1].title, None);
+    assert_eq!(reviews[1].author_avatar, None);
+    assert_eq!(client.novel_reviews(263060, 1).await?, None);
+
+    let replies = client.review_replies(701, 0).await?.unwrap();
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0].content, "写得真好");
+    assert_eq!(client.review_replies(701, 1).await?, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn author() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let author = client.author_info(1001).await?.unwrap();
+    assert_eq!(author.name, "测试作者");
+    assert!(author.avatar.is_some());
+    assert_eq!(
+        author.introduction,
+        Some(vec![String::from("第一行介绍"), String::from("第二行介绍")])
+    );
+    assert_eq!(author.follower_count, Some(321));
+    assert_eq!(client.author_info(1).await?, None);
+
+    let novels = client.author_novels(1001).await?;
+    assert_eq!(novels.len(), 1);
+    assert_eq!(novels[0].id, 263060);
+    assert_eq!(novels[0].author_name.as_deref(), Some("测试作者"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn reading_progress() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let progress = client.reading_progress(263060).await?.unwrap();
+    assert_eq!(progress.chapter_id, 9000002);
+    assert_eq!(
+        progress.read_time.map(|time| time.to_string()),
+        Some(String::from("2024-05-02 21:00:00"))
+    );
+    assert_eq!(client.reading_progress(1).await?, None);
+
+    client.set_reading_progress(263060, 9000003).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn search() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
 
     let categories = client.categories().await?;
-    println!("{categories:#?}");
+    assert_eq!(categories.len(), 2);
+    assert_eq!(categories[0].name, "魔幻");
 
     let tags = client.tags().await?;
-    println!("{tags:#?}");
+    assert_eq!(
+        tags.iter().map(|tag| tag.id.unwrap()).collect::<Vec<_>>(),
+        vec![1, 28, 74]
+    );
 
     let options = Options {
         word_count: Some(WordCountRange::RangeFrom(50_0000..)),
         ..Default::default()
     };
-    let novels = client.search_infos(&options, 0, 12).await?;
-    println!("{novels:#?}");
+    assert_eq!(
+        client.search_infos(&options, 0, 12).await?,
+        Some(vec![263060, 263061])
+    );
+    assert_eq!(client.search_infos(&options, 1, 12).await?, None);
+
+    let options = Options {
+        keyword: Some(String::from("测试")),
+        is_vip: Some(true),
+        ..Default::default()
+    };
+    assert_eq!(
+        client.search_infos(&options, 0, 12).await?,
+        Some(vec![263060])
+    );
+    assert_eq!(client.search_infos(&options, 1, 12).await?, None);
+
+    let options = Options {
+        author: Some(String::from("作者")),
+        ..Default::default()
+    };
+    assert!(matches!(
+        client.search_infos(&options, 0, 12).await,
+        Err(Error::Unsupported(_))
+    ));
+
+    let options = Options {
+        sort: Some(SortOrder::Sales),
+        ..Default::default()
+    };
+    assert!(matches!(
+        client.search_infos(&options, 0, 12).await,
+        Err(Error::Unsupported(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn search_stream() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let options = Options {
+        word_count: Some(WordCountRange::RangeFrom(50_0000..)),
+        ..Default::default()
+    };
+    let summaries = novel_api::search_novels(&client, &options, None)
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(summaries.len(), 2);
+
+    let summary = &summaries[0];
+    assert_eq!(summary.id, 263060);
+    assert_eq!(summary.name, "测试小说");
+    assert_eq!(summary.author_name.as_deref(), Some("作者"));
+    assert_eq!(summary.word_count, Some(623456));
+    assert_eq!(summary.is_vip, Some(true));
+    assert_eq!(summary.is_finished, Some(false));
+    assert_eq!(summary.category.as_ref().unwrap().name, "魔幻");
+    assert_eq!(summary.tags.as_ref().unwrap()[0].name, "百合");
+    assert_eq!(summaries[1].category.as_ref().unwrap().name, "科幻");
+    assert!(summaries[1].tags.is_none());
+
+    let summaries = novel_api::search_novels(&client, &options, Some(1))
+        .try_collect::<Vec<_>>()
+        .await?;
+    assert_eq!(summaries.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rankings() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let summaries = client
+        .rankings(RankingKind::Tickets, RankingPeriod::Month, 0)
+        .await?
+        .unwrap();
+    assert_eq!(
+        summaries
+            .iter()
+            .map(|summary| summary.id)
+            .collect::<Vec<_>>(),
+        vec![263061, 263060]
+    );
+    assert_eq!(summaries[0].author_name.as_deref(), Some("其他作者"));
+    assert_eq!(summaries[1].name, "测试小说");
+    assert_eq!(
+        client
+            .rankings(RankingKind::Tickets, RankingPeriod::Month, 1)
+            .await?,
+        None
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn cassette() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("sfacg.json");
+
+    let host = {
+        let server = MockServer::start(Router::new().fallback(handler)).await?;
+        let client = SfacgClient::builder()
+            .proxy(server.url().clone())
+            .host(server.url().clone())
+            .storage(Storage::root(dir.path()))
+            .cassette(Cassette::record(&path))
+            .build()
+            .await?;
+
+        assert_eq!(client.user_info().await?.nickname, "tester");
+        assert!(client.novel_info(263060).await?.is_some());
+        assert!(client.volume_infos(263060).await?.is_some());
+
+        server.url().clone()
+    };
+
+    assert!(std::fs::read_to_string(&path)?.contains(r#""json": {"#));
+
+    let client = SfacgClient::builder()
+        .no_proxy()
+        .host(host)
+        .storage(Storage::root(dir.path()))
+        .cassette(Cassette::replay(&path)?)
+        .build()
+        .await?;
+
+    assert_eq!(client.user_info().await?.nickname, "tester");
+    assert_eq!(client.novel_info(263060).await?.unwrap().name, "测试小说");
+    assert!(client.volume_infos(263060).await?.is_some());
+
+    assert!(matches!(
+        client.novel_info(263060).await,
+        Err(Error::Cassette(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn cached_metadata() -> Result<()> {
+    let (server, dir, client) = client().await?;
+    let storage = Storage::root(dir.path());
+
+    assert!(client.novel_info(263060).await?.is_some());
+    assert!(client.volume_infos(263060).await?.is_some());
+
+    let host = server.url().clone();
+    drop(server);
+
+    let client = SfacgClient::builder()
+        .no_proxy()
+        .host(host.clone())
+        .storage(storage.clone())
+        .build()
+        .await?;
+
+    let novel_info = client.cached_novel_info(263060).await?.unwrap();
+    assert_eq!(novel_info.name, "测试小说");
+
+    let volume_infos = client.cached_volume_infos(263060).await?.unwrap();
+    assert!(!volume_infos.is_empty());
+
+    let client = SfacgClient::builder()
+        .no_proxy()
+        .host(host.clone())
+        .storage(storage.clone())
+        .freshness(Freshness::MaxAge(Duration::from_secs(3600)))
+        .build()
+        .await?;
+    assert_eq!(client.novel_info(263060).await?.unwrap().name, "测试小说");
+    assert_eq!(
+        client.volume_infos(263060).await?.unwrap().len(),
+        volume_infos.len()
+    );
+
+    let client = SfacgClient::builder()
+        .no_proxy()
+        .host(host)
+        .storage(storage)
+        .freshness(Freshness::NoCache)
+        .retry_policy(RetryPolicy::none())
+        .build()
+        .await?;
+    assert!(client.novel_info(263060).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn offline() -> Result<()> {
+    let (server, dir, client) = client().await?;
+
+    assert_eq!(client.user_info().await?.nickname, "tester");
+    assert!(!client.bookshelf_infos().await?.is_empty());
+    assert!(client.novel_info(263060).await?.is_some());
+    let volume_infos = client.volume_infos(263060).await?.unwrap();
+    let chapter_info = &volume_infos[0].chapter_infos[0];
+    let content_infos = client.content_infos(chapter_info).await?;
+
+    drop(server);
+
+    let client = SfacgClient::builder()
+        .offline()
+        .storage(Storage::root(dir.path()))
+        .build()
+        .await?;
+
+    assert_eq!(client.user_info().await?.nickname, "tester");
+    assert!(!client.bookshelf_infos().await?.is_empty());
+    assert_eq!(client.novel_info(263060).await?.unwrap().name, "测试小说");
+    assert_eq!(
+        client.volume_infos(263060).await?.unwrap().len(),
+        volume_infos.len()
+    );
+    assert_eq!(
+        client.content_infos(chapter_info).await?.len(),
+        content_infos.len()
+    );
+
+    assert!(matches!(
+        client.novel_info(1).await,
+        Err(Error::NotCached(_))
+    ));
+    assert!(matches!(client.money().await, Err(Error::NotCached(_))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn download() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+    let client = Arc::new(client);
+
+    let options = DownloadOptions {
+        concurrency: 2,
+        ..Default::default()
+    };
+    let mut download = novel_api::download_novel(Arc::clone(&client), 263060, options.clone());
+
+    let mut started = Vec::new();
+    let mut finished = Vec::new();
+    let mut skipped = Vec::new();
+    while let Some(event) = download.recv().await {
+        match event {
+            DownloadEvent::Started { volume_infos } => assert_eq!(volume_infos.len(), 1),
+            DownloadEvent::ChapterStarted { chapter_id } => started.push(chapter_id),
+            DownloadEvent::ChapterFinished {
+                chapter_id,
+                content_infos,
+            } => {
+                assert_eq!(content_infos.len(), 3);
+                finished.push(chapter_id);
+            }
+            DownloadEvent::ChapterSkipped { chapter_id } => skipped.push(chapter_id),
+            DownloadEvent::ChapterFailed { chapter_id, error } => {
+                panic!("Failed to download chapter `{chapter_id}`: {error}")
+            }
+        }
+    }
+    download.wait().await?;
+
+    assert_eq!(started, vec![9000001]);
+    assert_eq!(finished, vec![9000001]);
+    assert_eq!(skipped, vec![9000002]);
+
+    let download = novel_api::download_novel(Arc::clone(&client), 1, options.clone());
+    assert!(matches!(download.wait().await, Err(Error::NotFound(_))));
+
+    let download = novel_api::download_novel(client, 263060, options);
+    download.cancel();
+    assert!(matches!(download.wait().await, Err(Error::Cancelled)));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_epub() -> Result<()> {
+    let (server, dir, client) = client().await?;
+
+    let novel_info = client.novel_info(263060).await?.unwrap();
+    let volume_infos = client.volume_infos(263060).await?.unwrap();
+    let client = Arc::new(client);
+    novel_api::download_novel(Arc::clone(&client), 263060, DownloadOptions::default())
+        .wait()
+        .await?;
+    client.image(novel_info.cover_url.as_ref().unwrap()).await?;
+
+    drop(server);
+
+    let client = SfacgClient::builder()
+        .offline()
+        .storage(Storage::root(dir.path()))
+        .build()
+        .await?;
+
+    let path = dir.path().join("novel.epub");
+    novel_api::export_epub(&client, &novel_info, &volume_infos, &path).await?;
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(&path)?)?;
+    let read = |archive: &mut zip::ZipArchive<fs::File>, name: &str| -> Result<String> {
+        let mut content = String::new();
+        archive.by_name(name)?.read_to_string(&mut content)?;
+        Ok(content)
+    };
+
+    let mimetype = archive.by_index(0)?;
+    assert_eq!(mimetype.name(), "mimetype");
+    assert_eq!(mimetype.compression(), zip::CompressionMethod::Stored);
+    drop(mimetype);
+    assert_eq!(read(&mut archive, "mimetype")?, "application/epub+zip");
+
+    let package = read(&mut archive, "OEBPS/content.opf")?;
+    assert!(package.contains("<dc:title>测试小说</dc:title>"));
+    assert!(package.contains("<dc:creator>测试作者</dc:creator>"));
+    assert!(package.contains("<dc:subject>百合</dc:subject>"));
+    assert!(package.contains(r#"properties="cover-image""#));
+
+    let nav = read(&mut archive, "OEBPS/nav.xhtml")?;
+    assert!(nav.contains(r#"<a href="text/chapter-9000001.xhtml">第一章</a>"#));
+    assert!(!nav.contains("chapter-9000002"));
+
+    let chapter = read(&mut archive, "OEBPS/text/chapter-9000001.xhtml")?;
+    assert!(chapter.contains("<p>第一段</p>"));
+    assert!(chapter.contains(r#"<img src="../images/1.jpg""#));
+
+    assert!(archive.by_name("OEBPS/images/1.jpg").is_ok());
+    assert!(archive.by_name("OEBPS/images/cover.jpg").is_ok());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn export_markdown_and_txt() -> Result<()> {
+    let (_server, _dir, client) = client().await?;
+
+    let novel_info = client.novel_info(263060).await?.unwrap();
+    let volume_infos = client.volume_infos(263060).await?.unwrap();
+    let dir = tempfile::tempdir()?;
+
+    let path = dir.path().join("novel.md");
+    let options = ExportOptions {
+        volume_heading_level: 2,
+        chapter_heading_level: 3,
+        ..Default::default()
+    };
+    novel_api::export_markdown(&client, &novel_info, &volume_infos, &path, &options).await?;
+
+    let markdown = fs::read_to_string(&path)?;
+    assert!(markdown.starts_with("---\ntitle: \"测试小说\"\nauthor: \"测试作者\"\n"));
+    assert!(markdown.contains("keywords:\n  - \"百合\"\n"));
+    assert!(markdown.contains("cover-image: \"novel-images/cover.webp\"\n"));
+    assert!(markdown.contains("> 第一行简介\n>\n> 第二行简介\n\n"));
+    assert!(markdown
+        .contains("## 第一卷\n\n### 第一章\n\n第一段\n\n![](novel-images/1.webp)\n\n第二段\n\n"));
+    assert!(!markdown.contains("第二章"));
+    assert!(dir.path().join("novel-images/cover.webp").is_file());
+    assert!(dir.path().join("novel-images/1.webp").is_file());
+
+    let path = dir.path().join("novel.txt");
+    let options = ExportOptions {
+        images: ImageMode::Remote,
+        introduction: false,
+        front_matter: false,
+        ..Default::default()
+    };
+    novel_api::export_txt(&client, &novel_info, &volume_infos, &path, &options).await?;
+
+    assert_eq!(
+        fs::read_to_string(&path)?,
+        format!(
+            "第一卷\n\n第一章\n\n第一段\n{}\n第二段\n",
+            common::image_url("/images/1.png")
+        )
+    );
+    assert!(!dir.path().join("novel-images/2.webp").exists());
+
+    Ok(())
+}
+
+async fn error_handler(uri: Uri) -> Response {
+    let name = match uri.path() {
+        "/user" => "unauthorized.json",
+        "/user/money" => "too_many_requests.json",
+        "/novels/263060/orderedchaps" => "insufficient_balance.json",
+        _ => "not_found.json",
+    };
+
+    common::json_response(common::fixture(SOURCE, name))
+}
+
+#[tokio::test]
+async fn errors() -> Result<()> {
+    let server = MockServer::start(Router::new().fallback(error_handler)).await?;
+    let dir = tempfile::tempdir()?;
+
+    let client = SfacgClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .storage(Storage::root(dir.path()))
+        .build()
+        .await?;
+
+    assert!(!client.logged_in().await?);
+    assert!(matches!(client.user_info().await, Err(Error::LoginExpired)));
+    assert!(matches!(client.money().await, Err(Error::RateLimited(_))));
+
+    let chapter_info = ChapterInfo {
+        novel_id: Some(263060),
+        id: 9000002,
+        ..Default::default()
+    };
+    assert!(matches!(
+        client.buy_chapter(&chapter_info).await,
+        Err(Error::InsufficientBalance(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn invalid_options() -> Result<()> {
+    let server = MockServer::start(Router::new().fallback(handler)).await?;
+    let dir = tempfile::tempdir()?;
+
+    assert!(matches!(
+        SfacgClient::builder()
+            .proxy(server.url().clone())
+            .no_proxy()
+            .build()
+            .await,
+        Err(Error::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        SfacgClient::builder()
+            .cert("/path/to/missing/cert.pem")
+            .build()
+            .await,
+        Err(Error::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        SfacgClient::builder().timeout(Duration::ZERO).build().await,
+        Err(Error::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        SfacgClient::builder()
+            .host(Url::parse("ftp://localhost")?)
+            .build()
+            .await,
+        Err(Error::InvalidOptions(_))
+    ));
+
+    let client = SfacgClient::builder()
+        .proxy(server.url().clone())
+        .host(server.url().clone())
+        .connect_timeout(Duration::from_secs(1))
+        .timeout(Duration::from_secs(5))
+        .user_agent("novel-api-test")
+        .storage(Storage::root(dir.path()))
+        .build()
+        .await?;
+    assert_eq!(client.money().await?, 120);
 
     Ok(())
 }